@@ -0,0 +1,443 @@
+use num::{BigUint, Integer, One, ToPrimitive, Zero};
+
+use crate::{
+    ecpp::{verify_ecpp_step, EcppStep},
+    prime::{is_prime_miller_rabin, is_prime_u64, small_odd_primes, SMALL_PRIMES_BOUND},
+};
+
+/// Miller-Rabin rounds used to discard composites before trying to prove them
+const PROBABLE_PRIME_ROUNDS: usize = 20;
+
+/// Pollard rho iterations spent on each composite part of n - 1
+pub const RHO_BUDGET: usize = 1 << 20;
+
+/// Number of bases tried when looking for a witness
+const WITNESS_SEARCH_LIMIT: u32 = 10_000;
+
+/// A proven prime factor q^e of n - 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factor {
+    pub prime: BigUint,
+    pub exponent: u32,
+    pub certificate: Box<Certificate>,
+}
+
+/// Machine-checkable proof that a number is prime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Certificate {
+    /// n < 2^64, checked with the deterministic Miller-Rabin bases
+    Small(u64),
+    /// Lucas-Lehmer-Pratt: `witness` has order n - 1 and n - 1 is completely
+    /// factored into `factors`
+    Pratt {
+        n: BigUint,
+        witness: BigUint,
+        factors: Vec<Factor>,
+    },
+    /// Pocklington / Brillhart-Lehmer-Selfridge: the factored part F of n - 1
+    /// satisfies F ≥ n^(1/3) and `witnesses[i]` proves the condition for
+    /// `factors[i]`
+    Pocklington {
+        n: BigUint,
+        witnesses: Vec<BigUint>,
+        factors: Vec<Factor>,
+    },
+    /// Atkin-Morain: `step` proves n prime if `step.q` is prime, which
+    /// `certificate` proves
+    Ecpp {
+        step: EcppStep,
+        certificate: Box<Certificate>,
+    },
+}
+
+impl Certificate {
+    /// The number proven prime by the certificate
+    pub fn n(&self) -> BigUint {
+        match self {
+            Certificate::Small(n) => BigUint::from(*n),
+            Certificate::Pratt { n, .. } | Certificate::Pocklington { n, .. } => n.clone(),
+            Certificate::Ecpp { step, .. } => step.n.clone(),
+        }
+    }
+}
+
+/// # Pollard's Rho (Brent)
+/// Looks for a non-trivial factor of the composite `n` with the map
+/// x -> x^2 + c, batching the gcds every 128 steps.
+///
+/// ## Arguments
+/// * `n` - The composite to split
+/// * `c` - The constant of the map (try another one on failure)
+/// * `budget` - The maximum number of iterations
+pub fn pollard_rho(n: &BigUint, c: u64, budget: usize) -> Option<BigUint> {
+    if n.is_even() {
+        return Some(BigUint::from(2u32));
+    }
+    let c = BigUint::from(c);
+    let step = |x: &BigUint| (x * x + &c) % n;
+    let mut y = BigUint::from(2u32);
+    let mut x = y.clone();
+    let mut saved = y.clone();
+    let mut product = BigUint::one();
+    let mut factor = BigUint::one();
+    let mut range = 1;
+    let mut iterations = 0;
+    while factor.is_one() && iterations < budget {
+        x = y.clone();
+        for _ in 0..range {
+            y = step(&y);
+        }
+        let mut k = 0;
+        while k < range && factor.is_one() {
+            saved = y.clone();
+            for _ in 0..128.min(range - k) {
+                y = step(&y);
+                let difference = if x > y { &x - &y } else { &y - &x };
+                product = product * difference % n;
+            }
+            factor = product.gcd(n);
+            k += 128;
+            iterations += 128.min(range);
+        }
+        range *= 2;
+    }
+    // The Batch Overshot, Backtrack One Step at a Time
+    if &factor == n {
+        loop {
+            saved = step(&saved);
+            let difference = if x > saved { &x - &saved } else { &saved - &x };
+            factor = difference.gcd(n);
+            if !factor.is_one() {
+                break;
+            }
+        }
+    }
+    if factor.is_one() || &factor == n {
+        None
+    } else {
+        Some(factor)
+    }
+}
+
+/// Splits `m` into proven prime powers and an unfactored cofactor
+fn factor_partial(mut m: BigUint) -> (Vec<Factor>, BigUint) {
+    let mut primes: Vec<(BigUint, u32)> = Vec::new();
+    // Trial Division
+    let exponent = m.trailing_zeros().unwrap_or(0) as u32;
+    if exponent > 0 {
+        m >>= exponent;
+        primes.push((BigUint::from(2u32), exponent));
+    }
+    for prime in small_odd_primes(SMALL_PRIMES_BOUND) {
+        let mut exponent = 0;
+        while (&m % prime).is_zero() {
+            m /= prime;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            primes.push((BigUint::from(prime), exponent));
+        }
+    }
+    // Pollard Rho on the Composite Parts
+    let mut cofactor = BigUint::one();
+    let mut parts = vec![m];
+    while let Some(part) = parts.pop() {
+        if part.is_one() {
+            continue;
+        }
+        if is_prime_miller_rabin(part.clone(), PROBABLE_PRIME_ROUNDS) {
+            match primes.iter_mut().find(|(prime, _)| prime == &part) {
+                Some((_, exponent)) => *exponent += 1,
+                None => primes.push((part, 1)),
+            }
+            continue;
+        }
+        match (1..4).find_map(|c| pollard_rho(&part, c, RHO_BUDGET)) {
+            Some(factor) => {
+                parts.push(&part / &factor);
+                parts.push(factor);
+            }
+            None => cofactor *= part,
+        }
+    }
+    // Prove the Factors, the Unprovable Ones Stay in the Cofactor
+    let mut factors = Vec::new();
+    for (prime, exponent) in primes {
+        match prove_prime(&prime) {
+            Some(certificate) => factors.push(Factor {
+                prime,
+                exponent,
+                certificate: Box::new(certificate),
+            }),
+            None => cofactor *= prime.pow(exponent),
+        }
+    }
+    (factors, cofactor)
+}
+
+/// Checks the Brillhart-Lehmer-Selfridge condition for n - 1 = F * R with
+/// n^(1/3) ≤ F: writing n = c2 F^2 + c1 F + 1, n is prime iff c1^2 - 4 c2 is
+/// not a square. Also accepts F^2 > n (Pocklington).
+fn factored_part_suffices(n: &BigUint, factored: &BigUint) -> bool {
+    if factored * factored > *n {
+        return true;
+    }
+    if factored * factored * factored < *n {
+        return false;
+    }
+    let quotient = (n - 1u32) / factored;
+    let (c2, c1) = quotient.div_rem(factored);
+    if &c2 >= factored {
+        return false;
+    }
+    let c1_squared = &c1 * &c1;
+    let four_c2 = c2 * 4u32;
+    if c1_squared < four_c2 {
+        return true;
+    }
+    let discriminant = c1_squared - four_c2;
+    let root = discriminant.sqrt();
+    &root * &root != discriminant
+}
+
+/// # Prove Prime
+/// Builds a primality certificate for `n` from a partial factorization of
+/// n - 1 (trial division and Pollard rho). Every prime factor is certified
+/// recursively. A Pratt certificate is produced when n - 1 factors completely,
+/// otherwise a Pocklington/BLS certificate when the factored part F reaches
+/// n^(1/3). Returns `None` if `n` is composite or if n - 1 cannot be factored
+/// far enough.
+///
+/// ## Arguments
+/// * `n` - The number to prove prime
+pub fn prove_prime(n: &BigUint) -> Option<Certificate> {
+    if let Some(small) = n.to_u64() {
+        return if is_prime_u64(small) {
+            Some(Certificate::Small(small))
+        } else {
+            None
+        };
+    }
+    if !is_prime_miller_rabin(n.clone(), PROBABLE_PRIME_ROUNDS) {
+        return None;
+    }
+    let (factors, cofactor) = factor_partial(n - 1u32);
+    if cofactor.is_one() {
+        prove_prime_pratt(n, factors)
+    } else {
+        prove_prime_pocklington(n, factors)
+    }
+}
+
+/// Looks for a generator of (Z/nZ)* given the complete factorization of n - 1
+fn prove_prime_pratt(n: &BigUint, factors: Vec<Factor>) -> Option<Certificate> {
+    let n_minus_one = n - 1u32;
+    for base in 2..WITNESS_SEARCH_LIMIT {
+        let witness = BigUint::from(base);
+        if !witness.modpow(&n_minus_one, n).is_one() {
+            return None;
+        }
+        let generates = factors
+            .iter()
+            .all(|factor| !witness.modpow(&(&n_minus_one / &factor.prime), n).is_one());
+        if generates {
+            return Some(Certificate::Pratt {
+                n: n.clone(),
+                witness,
+                factors,
+            });
+        }
+    }
+    None
+}
+
+/// Looks for one Pocklington witness per prime factor of F
+fn prove_prime_pocklington(n: &BigUint, factors: Vec<Factor>) -> Option<Certificate> {
+    let factored = factors.iter().fold(BigUint::one(), |acc, factor| {
+        acc * factor.prime.pow(factor.exponent)
+    });
+    if !factored_part_suffices(n, &factored) {
+        return None;
+    }
+    let n_minus_one = n - 1u32;
+    let mut witnesses = Vec::with_capacity(factors.len());
+    for factor in factors.iter() {
+        let exponent = &n_minus_one / &factor.prime;
+        let witness = (2..WITNESS_SEARCH_LIMIT)
+            .map(BigUint::from)
+            .find(|witness| {
+                let power = witness.modpow(&exponent, n);
+                !power.is_zero() && (&power - 1u32).gcd(n).is_one()
+            })?;
+        if !witness.modpow(&n_minus_one, n).is_one() {
+            return None;
+        }
+        witnesses.push(witness);
+    }
+    Some(Certificate::Pocklington {
+        n: n.clone(),
+        witnesses,
+        factors,
+    })
+}
+
+/// Checks that the factors are distinct, proven and divide n - 1. Returns
+/// their product.
+fn verify_factors(n_minus_one: &BigUint, factors: &[Factor]) -> Option<BigUint> {
+    let mut product = BigUint::one();
+    for (i, factor) in factors.iter().enumerate() {
+        if factor.exponent == 0
+            || factors[..i].iter().any(|other| other.prime == factor.prime)
+            || !verify_certificate(&factor.prime, &factor.certificate)
+        {
+            return None;
+        }
+        product *= factor.prime.pow(factor.exponent);
+    }
+    if (n_minus_one % &product).is_zero() {
+        Some(product)
+    } else {
+        None
+    }
+}
+
+/// # Verify Certificate
+/// Checks a certificate produced by `prove_prime` without trusting any
+/// probabilistic test: every claimed factor is checked recursively.
+///
+/// ## Arguments
+/// * `n` - The number the certificate should prove prime
+/// * `certificate` - The certificate
+pub fn verify_certificate(n: &BigUint, certificate: &Certificate) -> bool {
+    if certificate.n() != *n || *n < BigUint::from(2u32) {
+        return false;
+    }
+    match certificate {
+        Certificate::Small(small) => is_prime_u64(*small),
+        Certificate::Pratt {
+            witness, factors, ..
+        } => {
+            let n_minus_one = n - 1u32;
+            match verify_factors(&n_minus_one, factors) {
+                Some(product) if product == n_minus_one => {}
+                _ => return false,
+            }
+            witness.modpow(&n_minus_one, n).is_one()
+                && factors
+                    .iter()
+                    .all(|factor| !witness.modpow(&(&n_minus_one / &factor.prime), n).is_one())
+        }
+        Certificate::Pocklington {
+            witnesses, factors, ..
+        } => {
+            let n_minus_one = n - 1u32;
+            let factored = match verify_factors(&n_minus_one, factors) {
+                Some(product) => product,
+                None => return false,
+            };
+            witnesses.len() == factors.len()
+                && factored_part_suffices(n, &factored)
+                && factors
+                    .iter()
+                    .zip(witnesses.iter())
+                    .all(|(factor, witness)| {
+                        let power = witness.modpow(&(&n_minus_one / &factor.prime), n);
+                        witness.modpow(&n_minus_one, n).is_one()
+                            && !power.is_zero()
+                            && (&power - 1u32).gcd(n).is_one()
+                    })
+        }
+        Certificate::Ecpp { step, certificate } => {
+            verify_ecpp_step(step) && verify_certificate(&step.q, certificate)
+        }
+    }
+}
+
+#[test]
+fn test_pollard_rho() {
+    // 2^67 - 1 = 193707721 * 761838257287
+    let n = (BigUint::one() << 67) - 1u32;
+    let factor = pollard_rho(&n, 1, RHO_BUDGET).unwrap();
+    assert!(factor == BigUint::from(193707721u64) || factor == BigUint::from(761838257287u64));
+}
+
+#[test]
+fn test_prove_prime() {
+    use std::str::FromStr;
+    for value in [
+        // Mersenne Primes 2^89 - 1 and 2^127 - 1
+        "618970019642690137449562111",
+        "170141183460469231731687303715884105727",
+        // Proth Prime 3 * 2^189 + 1 and the First Prime After 10^30
+        "2353913150770005286438421033702874906038383291674012942337",
+        "1000000000000000000000000000057",
+    ] {
+        let n = BigUint::from_str(value).unwrap();
+        let certificate = prove_prime(&n).unwrap();
+        assert!(verify_certificate(&n, &certificate), "{}", value);
+    }
+    assert_eq!(
+        prove_prime(&BigUint::from(1_000_000_007u64)),
+        Some(Certificate::Small(1_000_000_007))
+    );
+    assert_eq!(prove_prime(&BigUint::from(1u32)), None);
+    // 2^128 + 1 = 59649589127497217 * 5704689200685129054721
+    assert_eq!(prove_prime(&((BigUint::one() << 128) + 1u32)), None);
+}
+
+#[test]
+fn test_verify_certificate_rejects_forgeries() {
+    use std::str::FromStr;
+    let n = BigUint::from_str("170141183460469231731687303715884105727").unwrap();
+    let certificate = prove_prime(&n).unwrap();
+    // Wrong Number
+    assert!(!verify_certificate(&(&n + 2u32), &certificate));
+    // Missing Factor
+    if let Certificate::Pratt {
+        witness,
+        mut factors,
+        ..
+    } = certificate.clone()
+    {
+        factors.pop();
+        let forged = Certificate::Pratt {
+            n: n.clone(),
+            witness,
+            factors,
+        };
+        assert!(!verify_certificate(&n, &forged));
+    }
+    // Composite Claimed as Small Prime
+    assert!(!verify_certificate(
+        &BigUint::from(561u32),
+        &Certificate::Small(561)
+    ));
+    // Composite n = 2^128 + 1 With a Fake Pocklington Certificate on 2^128
+    let composite: BigUint = (BigUint::one() << 128) + 1u32;
+    let forged = Certificate::Pocklington {
+        n: composite.clone(),
+        witnesses: vec![BigUint::from(3u32)],
+        factors: vec![Factor {
+            prime: BigUint::from(2u32),
+            exponent: 128,
+            certificate: Box::new(Certificate::Small(2)),
+        }],
+    };
+    assert!(!verify_certificate(&composite, &forged));
+    // n = 0 and n = 1 Are Rejected, Not Underflowed
+    for small in [0u32, 1] {
+        let n = BigUint::from(small);
+        let pratt = Certificate::Pratt {
+            n: n.clone(),
+            witness: BigUint::from(3u32),
+            factors: Vec::new(),
+        };
+        let pocklington = Certificate::Pocklington {
+            n: n.clone(),
+            witnesses: Vec::new(),
+            factors: Vec::new(),
+        };
+        assert!(!verify_certificate(&n, &pratt));
+        assert!(!verify_certificate(&n, &pocklington));
+    }
+}
@@ -0,0 +1,370 @@
+use num::integer::Roots;
+
+use crate::sieve::{base_primes, primes_up_to, sieve_segment, SEGMENT_SIZE};
+
+/// Below this bound π(x) is computed by sieving
+const SIEVE_THRESHOLD: u64 = 1 << 20;
+
+/// Euler–Mascheroni constant
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// Computes μ(m) and the least prime factor of m for every m ≤ `limit`
+/// (the least prime factor of 1 is taken as infinite)
+fn mobius_and_least_factor(limit: u64) -> (Vec<i8>, Vec<u64>) {
+    let size = limit as usize + 1;
+    let mut mobius: Vec<i8> = vec![1; size];
+    let mut least_factor: Vec<u64> = vec![u64::MAX; size];
+    for prime in primes_up_to(limit) {
+        let prime = prime as usize;
+        for multiple in (prime..size).step_by(prime) {
+            if least_factor[multiple] == u64::MAX {
+                least_factor[multiple] = prime as u64;
+            }
+            mobius[multiple] = -mobius[multiple];
+        }
+        for multiple in (prime * prime..size).step_by(prime * prime) {
+            mobius[multiple] = 0;
+        }
+    }
+    (mobius, least_factor)
+}
+
+/// Bit set of the values of a segment that were not crossed yet, with the
+/// number of values left in each block of 1024 bits. Counting reads the
+/// prefix sums of the blocks, which are refreshed by `update_prefix`.
+struct Counter {
+    words: Vec<u64>,
+    blocks: Vec<u32>,
+    prefix: Vec<u64>,
+    total: u64,
+}
+
+impl Counter {
+    /// Bits per counting block
+    const BLOCK_BITS: usize = 1024;
+
+    /// Creates a counter where all the `len` values are present
+    fn full(len: usize) -> Counter {
+        let mut words: Vec<u64> = vec![u64::MAX; len.div_ceil(64)];
+        if !len.is_multiple_of(64) {
+            *words.last_mut().unwrap() = (1u64 << (len % 64)) - 1;
+        }
+        let blocks: Vec<u32> = words
+            .chunks(Counter::BLOCK_BITS / 64)
+            .map(|chunk| chunk.iter().map(|word| word.count_ones()).sum())
+            .collect();
+        let mut counter = Counter {
+            words,
+            prefix: vec![0; blocks.len() + 1],
+            blocks,
+            total: len as u64,
+        };
+        counter.update_prefix();
+        counter
+    }
+
+    /// Removes the value at `idx`, if it is still present
+    fn remove(&mut self, idx: usize) {
+        // Branchless, as Whether the Value is Present is Unpredictable
+        let word = &mut self.words[idx / 64];
+        let bit = (*word >> (idx % 64)) & 1;
+        *word &= !(1u64 << (idx % 64));
+        self.blocks[idx / Counter::BLOCK_BITS] -= bit as u32;
+        self.total -= bit;
+    }
+
+    /// Recomputes the prefix sums of the blocks after removals
+    fn update_prefix(&mut self) {
+        for (idx, &count) in self.blocks.iter().enumerate() {
+            self.prefix[idx + 1] = self.prefix[idx] + count as u64;
+        }
+    }
+
+    /// Counts the values present in [0, idx]
+    fn count(&self, idx: usize) -> u64 {
+        let block = idx / Counter::BLOCK_BITS;
+        let first_word = block * Counter::BLOCK_BITS / 64;
+        let mut count = self.prefix[block];
+        for word in &self.words[first_word..idx / 64] {
+            count += word.count_ones() as u64;
+        }
+        let mask = u64::MAX >> (63 - idx % 64);
+        count + (self.words[idx / 64] & mask).count_ones() as u64
+    }
+
+    /// Counts all the values present
+    fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// Computes the special leaves S2 = -Σ μ(m) φ(x / (m·p_{b+1}), b), over the
+/// primes p_{b+1} ≤ y and the m ≤ y < m·p_{b+1} with lpf(m) > p_{b+1}. The
+/// values φ(v, b) are read from a segmented odd-only sieve of [1, x / y] that
+/// removes the multiples of p_2, p_3, ... in order (φ(v, 0) = v is direct).
+fn special_leaves(x: u64, y: u64, primes: &[u64], mobius: &[i8], least_factor: &[u64]) -> i128 {
+    let limit = x / y;
+    let segment_size = (y.max(limit.sqrt()) as usize).max(1 << 16);
+    // φ(low - 1, b) and the Next Odd Multiple of p_{b+1} for Each b
+    let mut phi: Vec<u64> = vec![0; primes.len()];
+    let mut next_multiple: Vec<u64> = primes.to_vec();
+    // Range [start, end) of the Leaf Values x / (m·p_{b+1}) for Each b
+    let leaves_start: Vec<u64> = primes.iter().map(|&prime| x / (prime * y)).collect();
+    let leaves_end: Vec<u64> = primes
+        .iter()
+        .map(|&prime| x / (prime * (y / prime).max(prime) + prime) + 1)
+        .collect();
+    let mut sum: i128 = 0;
+    let mut low: u64 = 1;
+    while low <= limit {
+        // Odd Values low, low + 2, ..., high - 2
+        let high = (low + 2 * segment_size as u64).min(limit + 2 - (limit + 1) % 2);
+        let len = ((high - low) / 2) as usize;
+        let mut counter = Counter::full(len);
+        for (b, &prime) in primes.iter().enumerate() {
+            // Leaves With x / (m·p) Inside the Segment, in Increasing Order
+            if leaves_end[b] > low && leaves_start[b] < high {
+                let m_min = (y / prime).max(x / (prime * high)) + 1;
+                let m_max = y.min(x / (prime * low));
+                if prime * prime > y {
+                    // Here m > y / p ≥ √y Has No Factor ≤ p, so it is a Prime q > p
+                    let first = primes.partition_point(|&q| q < m_min.max(prime + 1));
+                    let last = primes.partition_point(|&q| q <= m_max);
+                    if first < last {
+                        counter.update_prefix();
+                    }
+                    for &m in primes[first..last.max(first)].iter().rev() {
+                        let value = x / (prime * m);
+                        sum += (phi[b] + counter.count(((value - low) / 2) as usize)) as i128;
+                    }
+                } else {
+                    if b > 0 && m_min <= m_max {
+                        counter.update_prefix();
+                    }
+                    let mut m = m_max;
+                    while m >= m_min {
+                        if mobius[m as usize] != 0 && least_factor[m as usize] > prime {
+                            let value = x / (prime * m);
+                            let phi_value = if b == 0 {
+                                value
+                            } else {
+                                phi[b] + counter.count(((value - low) / 2) as usize)
+                            };
+                            sum -= mobius[m as usize] as i128 * phi_value as i128;
+                        }
+                        m -= 1;
+                    }
+                }
+            }
+            // Update φ(high - 1, b) and Cross the Odd Multiples of p_{b+1}
+            if b > 0 {
+                phi[b] += counter.total();
+                let mut multiple = next_multiple[b];
+                while multiple < high {
+                    counter.remove(((multiple - low) / 2) as usize);
+                    multiple += 2 * prime;
+                }
+                next_multiple[b] = multiple;
+            }
+        }
+        low = high;
+    }
+    sum
+}
+
+/// Computes P2(x, a) = Σ (π(x / p) - π(p) + 1) over the primes y < p ≤ √x,
+/// counting the primes up to x / y with a segmented sieve
+fn second_partial_sieve(x: u64, y: u64) -> u64 {
+    let sqrt = x.sqrt();
+    let sqrt_primes = primes_up_to(sqrt);
+    // Values x / p in Increasing Order, With π(p)
+    let targets: Vec<(u64, u64)> = sqrt_primes
+        .iter()
+        .enumerate()
+        .filter(|&(_, &prime)| prime > y)
+        .rev()
+        .map(|(idx, &prime)| (x / prime, idx as u64 + 1))
+        .collect();
+    let limit = x / y;
+    let odd_primes = base_primes(limit.sqrt());
+    let mut sum: u64 = 0;
+    // Counts Two Plus the Odd Primes Below the Segment
+    let mut count: u64 = 1;
+    let mut target = 0;
+    let mut low: u64 = 1;
+    while low <= limit && target < targets.len() {
+        let len = (((limit - low) / 2 + 1) as usize).min(SEGMENT_SIZE);
+        let segment = sieve_segment(low, len, &odd_primes);
+        let high = low + 2 * len as u64;
+        let mut position = 0;
+        while target < targets.len() && targets[target].0 < high {
+            let (value, prime_pi) = targets[target];
+            let idx = ((value - low) / 2) as usize + 1;
+            count += segment[position..idx].count_ones() as u64;
+            position = idx;
+            sum += count - prime_pi + 1;
+            target += 1;
+        }
+        count += segment[position..].count_ones() as u64;
+        low = high;
+    }
+    sum
+}
+
+/// # Prime Counting Function
+/// Computes π(x), the number of primes ≤ x, with the Lagarias–Miller–Odlyzko
+/// variant of the Meissel–Lehmer method: π(x) = φ(x, a) + a - 1 - P2(x, a)
+/// with a = π(y) and y ≈ x^(1/3). φ(x, a) is split into the ordinary leaves,
+/// summed directly, and the special leaves, read from a segmented sieve of
+/// [1, x / y]. It takes O(x^(2/3) log x) time and O(x^(1/3) log x) space.
+///
+/// ## Arguments
+/// * `x` - The upper bound of the count
+pub fn prime_count(x: u64) -> u64 {
+    if x < SIEVE_THRESHOLD {
+        return primes_up_to(x).len() as u64;
+    }
+    // Use y = α·x^(1/3), With α Growing Slowly With x
+    let alpha = ((x as f64).log10() / 4.0).max(1.0);
+    let y = (((x.cbrt() as f64) * alpha) as u64).min(x.sqrt());
+    let primes = primes_up_to(y);
+    let a = primes.len() as i128;
+    let (mobius, least_factor) = mobius_and_least_factor(y);
+    // Ordinary Leaves
+    let ordinary: i128 = (1..=y)
+        .map(|m| mobius[m as usize] as i128 * (x / m) as i128)
+        .sum();
+    let special = special_leaves(x, y, &primes, &mobius, &least_factor);
+    let p2 = second_partial_sieve(x, y) as i128;
+    (ordinary + special + a - 1 - p2) as u64
+}
+
+/// # Logarithmic Integral
+/// Computes li(x) = ∫₀ˣ dt / ln t with the series
+/// li(x) = γ + ln ln x + Σ (ln x)^k / (k · k!). It is 0 at 0 and NaN for
+/// negative or NaN x.
+pub fn li(x: f64) -> f64 {
+    // The Series Never Converges Outside (0, ∞)
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 || x.is_infinite() {
+        return x;
+    }
+    if x == 1.0 {
+        return f64::NEG_INFINITY;
+    }
+    let ln_x = x.ln();
+    let mut sum = 0.0;
+    let mut power = 1.0;
+    let mut k = 1.0;
+    loop {
+        power *= ln_x / k;
+        let term = power / k;
+        sum += term;
+        if term.abs() < 1e-17 * sum.abs() && k > ln_x.abs() {
+            break;
+        }
+        k += 1.0;
+    }
+    EULER_GAMMA + ln_x.abs().ln() + sum
+}
+
+/// Computes the Riemann zeta function ζ(s) for s ≥ 2 with Euler–Maclaurin
+/// summation
+fn zeta(s: f64) -> f64 {
+    const TERMS: u32 = 20;
+    let n = TERMS as f64;
+    let partial: f64 = (1..TERMS).map(|k| (k as f64).powf(-s)).sum();
+    partial + n.powf(1.0 - s) / (s - 1.0) + 0.5 * n.powf(-s) + s * n.powf(-s - 1.0) / 12.0
+        - s * (s + 1.0) * (s + 2.0) * n.powf(-s - 3.0) / 720.0
+}
+
+/// # Riemann R Function
+/// Computes R(x) = Σ μ(n) li(x^(1/n)) / n with the Gram series
+/// R(x) = 1 + Σ (ln x)^k / (k · k! · ζ(k + 1)). It is NaN for x ≤ 0 or NaN.
+pub fn riemann_r(x: f64) -> f64 {
+    // The Series Never Converges Outside (0, ∞)
+    if x.is_nan() || x <= 0.0 {
+        return f64::NAN;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    let ln_x = x.ln();
+    let mut sum = 1.0;
+    let mut power = 1.0;
+    let mut k = 1.0;
+    loop {
+        power *= ln_x / k;
+        let term = power / (k * zeta(k + 1.0));
+        sum += term;
+        if term.abs() < 1e-17 * sum.abs() && k > ln_x.abs() {
+            break;
+        }
+        k += 1.0;
+    }
+    sum
+}
+
+#[test]
+fn test_prime_count_small() {
+    let primes = primes_up_to(3_000_000);
+    for &x in [
+        0, 1, 2, 3, 10, 100, 1_048_575, 1_048_576, 2_000_003, 3_000_000,
+    ]
+    .iter()
+    {
+        let expected = primes.iter().take_while(|&&p| p <= x).count() as u64;
+        assert_eq!(prime_count(x), expected, "π({})", x);
+    }
+}
+
+#[test]
+fn test_prime_count_powers_of_ten() {
+    let expected: [u64; 8] = [
+        78498,
+        664579,
+        5761455,
+        50847534,
+        455052511,
+        4118054813,
+        37607912018,
+        346065536839,
+    ];
+    for (exp, &count) in expected.iter().enumerate() {
+        let x = 10u64.pow(exp as u32 + 6);
+        assert_eq!(prime_count(x), count, "π(10^{})", exp + 6);
+    }
+}
+
+#[test]
+fn test_prime_count_matches_sieve() {
+    use crate::sieve::prime_count_in_range_parallel;
+    for &x in [33_333_333, 123_456_789].iter() {
+        assert_eq!(
+            prime_count(x),
+            prime_count_in_range_parallel(0, x, 4),
+            "π({})",
+            x
+        );
+    }
+}
+
+#[test]
+fn test_li_and_riemann_r() {
+    // li(10^6) ≈ 78627.549, R(10^6) ≈ 78527.400
+    assert!((li(1e6) - 78627.549).abs() < 1e-2);
+    assert!((riemann_r(1e6) - 78527.400).abs() < 1e-2);
+    // R(x) is Closer to π(x) Than li(x)
+    let x = 1e9;
+    let pi = 50847534.0;
+    assert!((riemann_r(x) - pi).abs() < (li(x) - pi).abs());    // Outside (0, ∞) Both Return at Once
+    assert_eq!(li(0.0), 0.0);
+    assert!(li(-1.0).is_nan() && li(f64::NAN).is_nan());
+    assert_eq!(li(f64::INFINITY), f64::INFINITY);
+    assert!(riemann_r(0.0).is_nan() && riemann_r(-1.0).is_nan() && riemann_r(f64::NAN).is_nan());
+    assert_eq!(riemann_r(f64::INFINITY), f64::INFINITY);
+    // Between 0 and 1 li is Negative
+    assert!((li(0.5) + 0.378671).abs() < 1e-6);
+}
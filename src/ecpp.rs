@@ -0,0 +1,842 @@
+use std::{collections::HashMap, fmt};
+
+use num::{BigInt, BigUint, Integer, One, Signed, Zero};
+
+use crate::{
+    certificate::{prove_prime, Certificate},
+    prime::{is_prime_miller_rabin, jacobi_symbol, small_odd_primes},
+};
+
+/// Largest |D| considered for the complex multiplication discriminants
+pub const DISCRIMINANT_LIMIT: u64 = 20_000;
+
+/// Largest class number (degree of the Hilbert class polynomial) considered
+pub const CLASS_NUMBER_LIMIT: usize = 48;
+
+/// Small primes removed from the candidate curve orders
+const ORDER_TRIAL_BOUND: u32 = 1 << 16;
+
+/// Miller-Rabin rounds used to pick the next number of the chain
+const PROBABLE_PRIME_ROUNDS: usize = 8;
+
+/// Points tried on each curve before giving up
+const POINT_ATTEMPTS: u64 = 64;
+
+/// Reasons an ECPP certificate could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcppError {
+    /// The number (or a number of the chain) is composite
+    Composite(BigUint),
+    /// No discriminant up to `DISCRIMINANT_LIMIT` with class number up to
+    /// `CLASS_NUMBER_LIMIT` gives a usable curve order for this number of the chain
+    NoDiscriminant(BigUint),
+}
+
+impl fmt::Display for EcppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcppError::Composite(n) => write!(f, "{} is composite", n),
+            EcppError::NoDiscriminant(n) => write!(f, "No discriminant works for {}", n),
+        }
+    }
+}
+
+/// One link of an Atkin-Morain chain: the curve y^2 = x^3 + a x + b over Z/nZ
+/// has a point P = (x, y) with [m]P = O and [m/q]P ≠ O, so n is prime if q is
+/// prime and q > (n^(1/4) + 1)^2 (Goldwasser-Kilian).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcppStep {
+    pub n: BigUint,
+    pub a: BigUint,
+    pub b: BigUint,
+    pub m: BigUint,
+    pub q: BigUint,
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+/// A point of an elliptic curve in affine coordinates
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+/// Short Weierstrass curve over Z/nZ. The group law returns `None` when an
+/// inversion fails, which can only happen if n is composite.
+struct Curve<'a> {
+    n: &'a BigUint,
+    a: &'a BigUint,
+}
+
+impl<'a> Curve<'a> {
+    fn add(&self, p: &Point, q: &Point) -> Option<Point> {
+        let n = self.n;
+        let (x1, y1, x2, y2) = match (p, q) {
+            (Point::Infinity, _) => return Some(q.clone()),
+            (_, Point::Infinity) => return Some(p.clone()),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => (x1, y1, x2, y2),
+        };
+        let slope = if x1 == x2 {
+            if ((y1 + y2) % n).is_zero() {
+                return Some(Point::Infinity);
+            }
+            if y1 != y2 {
+                return None;
+            }
+            // Tangent: (3 x^2 + a) / 2y
+            (BigUint::from(3u32) * x1 * x1 + self.a) * mod_inverse(&(y1 << 1), n)? % n
+        } else {
+            sub_mod(y2, y1, n) * mod_inverse(&sub_mod(x2, x1, n), n)? % n
+        };
+        let x3 = sub_mod(&sub_mod(&(&slope * &slope % n), x1, n), x2, n);
+        let y3 = sub_mod(&(&slope * sub_mod(x1, &x3, n) % n), y1, n);
+        Some(Point::Affine(x3, y3))
+    }
+
+    fn mul(&self, p: &Point, k: &BigUint) -> Option<Point> {
+        let mut result = Point::Infinity;
+        for bit in (0..k.bits()).rev() {
+            result = self.add(&result, &result)?;
+            if k.bit(bit) {
+                result = self.add(&result, p)?;
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Computes a - b mod n for a, b < n
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= b {
+        a - b
+    } else {
+        n - b + a
+    }
+}
+
+/// Computes a^-1 mod n with the extended Euclidean algorithm
+fn mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let modulus = BigInt::from(n.clone());
+    let (mut r0, mut r1) = (modulus.clone(), BigInt::from(a % n));
+    let (mut t0, mut t1) = (BigInt::zero(), BigInt::one());
+    while !r1.is_zero() {
+        let quotient = &r0 / &r1;
+        let r2 = &r0 - &quotient * &r1;
+        r0 = std::mem::replace(&mut r1, r2);
+        let t2 = &t0 - &quotient * &t1;
+        t0 = std::mem::replace(&mut t1, t2);
+    }
+    if r0.is_one() {
+        t0.mod_floor(&modulus).to_biguint()
+    } else {
+        None
+    }
+}
+
+/// Computes a square root of a modulo the (probable) prime p with
+/// Tonelli-Shanks. Returns `None` if a is not a square.
+fn sqrt_mod(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let a = a % p;
+    if a.is_zero() {
+        return Some(a);
+    }
+    if jacobi_symbol(&a, p) != 1 {
+        return None;
+    }
+    // Write p - 1 = q * 2^s
+    let p_minus_one = p - 1u32;
+    let s = p_minus_one.trailing_zeros().unwrap();
+    let q = &p_minus_one >> s;
+    let non_residue = (2u32..10_000)
+        .map(BigUint::from)
+        .find(|z| jacobi_symbol(z, p) == -1)?;
+    let mut m = s;
+    let mut c = non_residue.modpow(&q, p);
+    let mut t = a.modpow(&q, p);
+    let mut root = a.modpow(&((&q + 1u32) >> 1), p);
+    while !t.is_one() {
+        // Find the Least i With t^(2^i) = 1
+        let mut i = 0;
+        let mut power = t.clone();
+        while !power.is_one() {
+            power = &power * &power % p;
+            i += 1;
+            if i == m {
+                return None;
+            }
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        root = root * b % p;
+    }
+    if &root * &root % p == a {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// Solves u^2 + d v^2 = 4n with the modified Cornacchia algorithm
+fn cornacchia(d: u64, n: &BigUint) -> Option<(BigUint, BigUint)> {
+    let mut root = sqrt_mod(&(n - d % n), n)?;
+    if root.is_odd() != d.is_odd() {
+        root = n - root;
+    }
+    let four_n: BigUint = n << 2;
+    let limit = four_n.sqrt();
+    let (mut a, mut b) = (n << 1, root);
+    while b > limit {
+        let r = &a % &b;
+        a = std::mem::replace(&mut b, r);
+    }
+    let rest = four_n - &b * &b;
+    if !(&rest % d).is_zero() {
+        return None;
+    }
+    let c = rest / d;
+    let v = c.sqrt();
+    if &v * &v == c {
+        Some((b, v))
+    } else {
+        None
+    }
+}
+
+/// (|D|, h(D)) for the usable discriminants, cheapest Hilbert polynomial
+/// first. D = -3 and D = -4 are skipped (j = 0 and j = 1728).
+fn discriminants() -> Vec<(u64, usize)> {
+    let limit = DISCRIMINANT_LIMIT as i64;
+    let mut class_numbers = vec![0usize; limit as usize + 1];
+    // Count the Primitive Reduced Forms (a, b, c): |b| ≤ a ≤ c
+    let mut a = 1i64;
+    while 3 * a * a <= limit {
+        for b in (1 - a)..=a {
+            let mut c = a;
+            while 4 * a * c - b * b <= limit {
+                if !(b < 0 && a == c) && a.gcd(&b).gcd(&c) == 1 {
+                    class_numbers[(4 * a * c - b * b) as usize] += 1;
+                }
+                c += 1;
+            }
+        }
+        a += 1;
+    }
+    let mut list: Vec<(u64, usize)> = class_numbers
+        .into_iter()
+        .enumerate()
+        .filter(|&(d, h)| d > 4 && h > 0 && h <= CLASS_NUMBER_LIMIT)
+        .map(|(d, h)| (d as u64, h))
+        .collect();
+    list.sort_by_key(|&(d, h)| (h, d));
+    list
+}
+
+/// The primitive reduced forms (a, b, c) of discriminant -d
+fn reduced_forms(d: u64) -> Vec<(i64, i64, i64)> {
+    let d = d as i64;
+    let mut forms = Vec::new();
+    let mut a = 1i64;
+    while 3 * a * a <= d {
+        for b in (1 - a)..=a {
+            if (b * b + d) % (4 * a) != 0 {
+                continue;
+            }
+            let c = (b * b + d) / (4 * a);
+            if c >= a && !(b < 0 && a == c) && a.gcd(&b).gcd(&c) == 1 {
+                forms.push((a, b, c));
+            }
+        }
+        a += 1;
+    }
+    forms
+}
+
+/// Complex number in fixed point (scaled by 2^precision)
+#[derive(Debug, Clone)]
+struct Complex {
+    re: BigInt,
+    im: BigInt,
+}
+
+impl Complex {
+    fn real(re: BigInt) -> Complex {
+        Complex {
+            re,
+            im: BigInt::zero(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    fn add(&self, other: &Complex) -> Complex {
+        Complex {
+            re: &self.re + &other.re,
+            im: &self.im + &other.im,
+        }
+    }
+
+    fn sub(&self, other: &Complex) -> Complex {
+        Complex {
+            re: &self.re - &other.re,
+            im: &self.im - &other.im,
+        }
+    }
+
+    fn mul(&self, other: &Complex, precision: usize) -> Complex {
+        Complex {
+            re: (&self.re * &other.re - &self.im * &other.im) >> precision,
+            im: (&self.re * &other.im + &self.im * &other.re) >> precision,
+        }
+    }
+
+    fn div(&self, other: &Complex, precision: usize) -> Complex {
+        let norm = &other.re * &other.re + &other.im * &other.im;
+        Complex {
+            re: ((&self.re * &other.re + &self.im * &other.im) << precision) / &norm,
+            im: ((&self.im * &other.re - &self.re * &other.im) << precision) / &norm,
+        }
+    }
+
+    fn div_integer(&self, k: i64) -> Complex {
+        Complex {
+            re: &self.re / k,
+            im: &self.im / k,
+        }
+    }
+}
+
+/// Computes π in fixed point with Machin's formula
+fn fixed_pi(precision: usize) -> BigInt {
+    let atan_inverse = |x: u32| {
+        // atan(1/x) = Σ (-1)^k / ((2k + 1) x^(2k + 1))
+        let mut power = (BigInt::one() << precision) / x;
+        let mut sum = BigInt::zero();
+        let mut k = 0u32;
+        while !power.is_zero() {
+            let term = &power / (2 * k + 1);
+            if k.is_multiple_of(2) {
+                sum += term;
+            } else {
+                sum -= term;
+            }
+            power /= x * x;
+            k += 1;
+        }
+        sum
+    };
+    atan_inverse(5) * 16 - atan_inverse(239) * 4
+}
+
+/// Computes e^z in fixed point: halve z, sum the Taylor series, square back
+fn fixed_exp(z: &Complex, precision: usize) -> Complex {
+    let magnitude_bits = (z.re.abs() + z.im.abs()).bits() as i64 - precision as i64;
+    let halvings = (magnitude_bits + 8).max(0) as usize;
+    let reduced = Complex {
+        re: &z.re >> halvings,
+        im: &z.im >> halvings,
+    };
+    let mut sum = Complex::real(BigInt::one() << precision);
+    let mut term = sum.clone();
+    let mut k = 1;
+    loop {
+        term = term.mul(&reduced, precision).div_integer(k);
+        if term.is_zero() {
+            break;
+        }
+        sum = sum.add(&term);
+        k += 1;
+    }
+    for _ in 0..halvings {
+        sum = sum.mul(&sum, precision);
+    }
+    sum
+}
+
+/// Computes Π (1 - q^n) with Euler's pentagonal number theorem
+fn euler_product(q: &Complex, precision: usize) -> Complex {
+    let mut sum = Complex::real(BigInt::one() << precision);
+    let mut q_n = q.clone();
+    let mut low = q.clone();
+    let mut n = 1;
+    while !low.is_zero() {
+        // q^(n(3n - 1)/2) and q^(n(3n + 1)/2)
+        let high = low.mul(&q_n, precision);
+        let pair = low.add(&high);
+        sum = if n % 2 == 1 {
+            sum.sub(&pair)
+        } else {
+            sum.add(&pair)
+        };
+        low = high
+            .mul(&q_n, precision)
+            .mul(&q_n, precision)
+            .mul(q, precision);
+        q_n = q_n.mul(q, precision);
+        n += 1;
+    }
+    sum
+}
+
+/// Computes j((-b + i√d) / 2a) as (256 f + 1)^3 / f with f = Δ(2τ) / Δ(τ)
+fn j_invariant(a: i64, b: i64, pi: &BigInt, sqrt_d: &BigInt, precision: usize) -> Complex {
+    // q = e^(2πiτ) = e^((-π√d - iπb) / a)
+    let z = Complex {
+        re: -((pi * sqrt_d) >> precision) / a,
+        im: -(pi * b) / a,
+    };
+    let q = fixed_exp(&z, precision);
+    let q_squared = q.mul(&q, precision);
+    let ratio = euler_product(&q_squared, precision).div(&euler_product(&q, precision), precision);
+    let ratio_3 = ratio.mul(&ratio, precision).mul(&ratio, precision);
+    let ratio_6 = ratio_3.mul(&ratio_3, precision);
+    let ratio_12 = ratio_6.mul(&ratio_6, precision);
+    let ratio_24 = ratio_12.mul(&ratio_12, precision);
+    let f = q.mul(&ratio_24, precision);
+    let g = Complex {
+        re: &f.re * 256 + (BigInt::one() << precision),
+        im: &f.im * 256,
+    };
+    g.mul(&g, precision).mul(&g, precision).div(&f, precision)
+}
+
+/// Multiplies the j-invariants at the given precision. Returns `None` if the
+/// coefficients are not close enough to integers.
+fn hilbert_at_precision(
+    d: u64,
+    forms: &[(i64, i64, i64)],
+    precision: usize,
+) -> Option<Vec<BigInt>> {
+    let pi = fixed_pi(precision);
+    let sqrt_d = BigInt::from((BigUint::from(d) << (2 * precision)).sqrt());
+    let one = BigInt::one() << precision;
+    let mut poly = vec![Complex::real(one.clone())];
+    for &(a, b, _) in forms {
+        let j = j_invariant(a, b, &pi, &sqrt_d, precision);
+        // Multiply by (X - j)
+        let mut next = vec![Complex::real(BigInt::zero()); poly.len() + 1];
+        for (i, coefficient) in poly.iter().enumerate() {
+            next[i + 1] = next[i + 1].add(coefficient);
+            next[i] = next[i].sub(&coefficient.mul(&j, precision));
+        }
+        poly = next;
+    }
+    let tolerance = BigInt::one() << (precision - 32);
+    poly.iter()
+        .map(|coefficient| {
+            let rounded: BigInt = (&coefficient.re + (&one >> 1usize)).div_floor(&one);
+            let error = &coefficient.re - &rounded * &one;
+            if error.abs() > tolerance || coefficient.im.abs() > tolerance {
+                None
+            } else {
+                Some(rounded)
+            }
+        })
+        .collect()
+}
+
+/// # Hilbert Class Polynomial
+/// Computes H_D(X) = Π (X - j(τ)) over the primitive reduced forms of
+/// discriminant D = -d, evaluating j in fixed point with enough bits to round
+/// the coefficients. Coefficients are returned from the constant term up.
+///
+/// ## Arguments
+/// * `d` - The absolute value of the discriminant (d ≡ 0, 3 mod 4)
+pub fn hilbert_class_polynomial(d: u64) -> Vec<BigInt> {
+    let forms = reduced_forms(d);
+    // log2 |j(τ)| ≈ π√d / (a ln 2)
+    let bits = std::f64::consts::PI * (d as f64).sqrt() / std::f64::consts::LN_2;
+    let inverse_sum: f64 = forms.iter().map(|&(a, _, _)| 1.0 / a as f64).sum();
+    let mut precision = (bits * (inverse_sum + 2.0)) as usize + 16 * forms.len() + 128;
+    loop {
+        if let Some(poly) = hilbert_at_precision(d, &forms, precision) {
+            return poly;
+        }
+        precision *= 2;
+    }
+}
+
+/// Polynomial arithmetic over Z/nZ, coefficients from the constant term up
+mod poly {
+    use num::{BigUint, One, Zero};
+
+    use super::{mod_inverse, sub_mod};
+
+    pub fn trim(p: &mut Vec<BigUint>) {
+        while p.last().is_some_and(Zero::is_zero) {
+            p.pop();
+        }
+    }
+
+    pub fn sub(a: &[BigUint], b: &[BigUint], n: &BigUint) -> Vec<BigUint> {
+        let zero = BigUint::zero();
+        let mut result: Vec<BigUint> = (0..a.len().max(b.len()))
+            .map(|i| sub_mod(a.get(i).unwrap_or(&zero), b.get(i).unwrap_or(&zero), n))
+            .collect();
+        trim(&mut result);
+        result
+    }
+
+    pub fn rem(a: &[BigUint], m: &[BigUint], n: &BigUint) -> Option<Vec<BigUint>> {
+        let inverse = mod_inverse(m.last()?, n)?;
+        let mut r = a.to_vec();
+        trim(&mut r);
+        while r.len() >= m.len() {
+            let coefficient = r.last().unwrap() * &inverse % n;
+            let shift = r.len() - m.len();
+            for (i, c) in m.iter().enumerate() {
+                r[shift + i] = sub_mod(&r[shift + i], &(&coefficient * c % n), n);
+            }
+            r.pop();
+            trim(&mut r);
+        }
+        Some(r)
+    }
+
+    pub fn mul_mod(
+        a: &[BigUint],
+        b: &[BigUint],
+        m: &[BigUint],
+        n: &BigUint,
+    ) -> Option<Vec<BigUint>> {
+        if a.is_empty() || b.is_empty() {
+            return Some(Vec::new());
+        }
+        let mut product = vec![BigUint::zero(); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] += x * y;
+            }
+        }
+        for coefficient in product.iter_mut() {
+            *coefficient %= n;
+        }
+        rem(&product, m, n)
+    }
+
+    pub fn pow_mod(
+        base: &[BigUint],
+        exp: &BigUint,
+        m: &[BigUint],
+        n: &BigUint,
+    ) -> Option<Vec<BigUint>> {
+        let base = rem(base, m, n)?;
+        let mut result = rem(&[BigUint::one()], m, n)?;
+        for bit in (0..exp.bits()).rev() {
+            result = mul_mod(&result, &result, m, n)?;
+            if exp.bit(bit) {
+                result = mul_mod(&result, &base, m, n)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Monic greatest common divisor
+    pub fn gcd(mut a: Vec<BigUint>, mut b: Vec<BigUint>, n: &BigUint) -> Option<Vec<BigUint>> {
+        trim(&mut a);
+        trim(&mut b);
+        while !b.is_empty() {
+            let r = rem(&a, &b, n)?;
+            a = std::mem::replace(&mut b, r);
+        }
+        let inverse = mod_inverse(a.last()?, n)?;
+        Some(a.iter().map(|c| c * &inverse % n).collect())
+    }
+}
+
+/// Finds a root of the polynomial h modulo the (probable) prime n with
+/// Cantor-Zassenhaus
+fn find_root(h: &[BigUint], n: &BigUint) -> Option<BigUint> {
+    let x = vec![BigUint::zero(), BigUint::one()];
+    // Keep the Linear Factors: gcd(X^n - X, h)
+    let x_n = poly::pow_mod(&x, n, h, n)?;
+    let mut g = poly::gcd(h.to_vec(), poly::sub(&x_n, &x, n), n)?;
+    if g.len() < 2 {
+        return None;
+    }
+    // Split With gcd((X + δ)^((n - 1)/2) - 1, g)
+    let half = (n - 1u32) >> 1;
+    let mut delta = 1u32;
+    while g.len() > 2 {
+        let power = poly::pow_mod(&[BigUint::from(delta), BigUint::one()], &half, &g, n)?;
+        let factor = poly::gcd(g.clone(), poly::sub(&power, &[BigUint::one()], n), n)?;
+        if factor.len() > 1 && factor.len() < g.len() {
+            g = factor;
+        }
+        delta += 1;
+    }
+    Some(sub_mod(&BigUint::zero(), &g[0], n))
+}
+
+/// Smallest q accepted for a step: (⌊n^(1/4)⌋ + 2)^2 > (n^(1/4) + 1)^2
+fn q_lower_bound(n: &BigUint) -> BigUint {
+    let root = n.nth_root(4) + 2u32;
+    &root * &root
+}
+
+/// Looks for a point proving that the curve with invariant j (or its twist)
+/// has order m
+fn curve_with_order(n: &BigUint, j: &BigUint, m: &BigUint, q: &BigUint) -> Option<EcppStep> {
+    let j_1728 = BigUint::from(1728u32) % n;
+    if j.is_zero() || *j == j_1728 {
+        return None;
+    }
+    // y^2 = x^3 + 3k x + 2k With k = j / (1728 - j) has Invariant j
+    let k = j * mod_inverse(&sub_mod(&j_1728, j, n), n)? % n;
+    let a = &k * 3u32 % n;
+    let b = &k * 2u32 % n;
+    let non_residue = (2u32..10_000)
+        .map(BigUint::from)
+        .find(|g| jacobi_symbol(g, n) == -1)?;
+    let g_2 = &non_residue * &non_residue % n;
+    let g_3 = &g_2 * &non_residue % n;
+    let cofactor = m / q;
+    let twist = (&a * &g_2 % n, &b * &g_3 % n);
+    for (a, b) in [(a, b), twist] {
+        let curve = Curve { n, a: &a };
+        for x in 0..POINT_ATTEMPTS {
+            let x = BigUint::from(x);
+            let rhs = (&x * &x * &x + &a * &x + &b) % n;
+            if jacobi_symbol(&rhs, n) != 1 {
+                continue;
+            }
+            let y = sqrt_mod(&rhs, n)?;
+            let point = Point::Affine(x.clone(), y.clone());
+            let u = curve.mul(&point, &cofactor)?;
+            if u == Point::Infinity {
+                continue;
+            }
+            if curve.mul(&u, q)? == Point::Infinity {
+                return Some(EcppStep {
+                    n: n.clone(),
+                    a,
+                    b,
+                    m: m.clone(),
+                    q: q.clone(),
+                    x,
+                    y,
+                });
+            }
+            // Wrong Curve, Try the Twist
+            break;
+        }
+    }
+    None
+}
+
+/// Finds one step of the chain: a discriminant for which 4n = u^2 + |D| v^2
+/// and one of the orders n + 1 ± u is a small cofactor times a probable prime
+fn ecpp_step(
+    n: &BigUint,
+    discriminants: &[(u64, usize)],
+    small_primes: &[u32],
+    polynomials: &mut HashMap<u64, Vec<BigInt>>,
+) -> Option<EcppStep> {
+    // Every Step Must Remove at Least One Bit
+    let bound = q_lower_bound(n);
+    let ceiling: BigUint = n >> 1;
+    let modulus = BigInt::from(n.clone());
+    for &(d, _) in discriminants {
+        if jacobi_symbol(&(n - d), n) != 1 {
+            continue;
+        }
+        let (u, _) = match cornacchia(d, n) {
+            Some(solution) => solution,
+            None => continue,
+        };
+        let mut root: Option<Option<BigUint>> = None;
+        for m in [n + 1u32 - &u, n + 1u32 + &u] {
+            // Remove the Small Factors
+            let mut q = m.clone();
+            for &prime in small_primes.iter().chain([2u32].iter()) {
+                while (&q % prime).is_zero() {
+                    q /= prime;
+                }
+            }
+            if q <= bound || q > ceiling || !is_prime_miller_rabin(q.clone(), PROBABLE_PRIME_ROUNDS)
+            {
+                continue;
+            }
+            let j = root.get_or_insert_with(|| {
+                let h = polynomials
+                    .entry(d)
+                    .or_insert_with(|| hilbert_class_polynomial(d));
+                let h: Vec<BigUint> = h
+                    .iter()
+                    .map(|c| c.mod_floor(&modulus).to_biguint().unwrap())
+                    .collect();
+                find_root(&h, n)
+            });
+            let j = match j {
+                Some(j) => j,
+                None => break,
+            };
+            if let Some(step) = curve_with_order(n, j, &m, &q) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// # Prove Prime (ECPP)
+/// Builds an Atkin-Morain certificate chain for `n`. Each step finds a curve
+/// with complex multiplication by an imaginary quadratic order whose order
+/// m = k q has a probable prime q > (n^(1/4) + 1)^2, and continues with q
+/// until it fits in 64 bits. Fails if `n` is composite or if no discriminant
+/// of the table works for some number of the chain.
+///
+/// ## Arguments
+/// * `n` - The number to prove prime
+pub fn prove_prime_ecpp(n: &BigUint) -> Result<Certificate, EcppError> {
+    prove_prime_ecpp_with(n, &discriminants())
+}
+
+/// Builds the chain using only the given discriminants
+fn prove_prime_ecpp_with(
+    n: &BigUint,
+    discriminants: &[(u64, usize)],
+) -> Result<Certificate, EcppError> {
+    let small_primes = small_odd_primes(ORDER_TRIAL_BOUND);
+    let mut polynomials = HashMap::new();
+    let mut steps = Vec::new();
+    let mut current = n.clone();
+    while current.bits() > 64 {
+        if !is_prime_miller_rabin(current.clone(), PROBABLE_PRIME_ROUNDS) {
+            return Err(EcppError::Composite(current));
+        }
+        let step = ecpp_step(&current, discriminants, &small_primes, &mut polynomials)
+            .ok_or_else(|| EcppError::NoDiscriminant(current.clone()))?;
+        current = step.q.clone();
+        steps.push(step);
+    }
+    let mut certificate = prove_prime(&current).ok_or(EcppError::Composite(current))?;
+    for step in steps.into_iter().rev() {
+        certificate = Certificate::Ecpp {
+            step,
+            certificate: Box::new(certificate),
+        };
+    }
+    Ok(certificate)
+}
+
+/// # Verify ECPP Step
+/// Checks one link of the chain with the pseudo-curve arithmetic: the curve is
+/// non-singular, P lies on it, q > (n^(1/4) + 1)^2, [m/q]P ≠ O and [m]P = O
+/// without any failed inversion. The primality of q is checked separately.
+///
+/// ## Arguments
+/// * `step` - The step to check
+pub fn verify_ecpp_step(step: &EcppStep) -> bool {
+    let n = &step.n;
+    if n.is_one() || !n.gcd(&BigUint::from(6u32)).is_one() {
+        return false;
+    }
+    if [&step.a, &step.b, &step.x, &step.y].iter().any(|&v| v >= n) {
+        return false;
+    }
+    // Non-Singular: gcd(4a^3 + 27b^2, n) = 1
+    let singular = (&step.a * &step.a * &step.a * 4u32 + &step.b * &step.b * 27u32) % n;
+    if !singular.gcd(n).is_one() {
+        return false;
+    }
+    // P on the Curve
+    let rhs = (&step.x * &step.x * &step.x + &step.a * &step.x + &step.b) % n;
+    if &step.y * &step.y % n != rhs {
+        return false;
+    }
+    if step.q.is_zero() || !(&step.m % &step.q).is_zero() || step.q <= q_lower_bound(n) {
+        return false;
+    }
+    let curve = Curve { n, a: &step.a };
+    let point = Point::Affine(step.x.clone(), step.y.clone());
+    match curve.mul(&point, &(&step.m / &step.q)) {
+        Some(Point::Infinity) | None => false,
+        Some(u) => curve.mul(&u, &step.q) == Some(Point::Infinity),
+    }
+}
+
+#[test]
+fn test_hilbert_class_polynomial() {
+    let to_big = |coefficients: &[i64]| -> Vec<BigInt> {
+        coefficients.iter().map(|&c| BigInt::from(c)).collect()
+    };
+    assert_eq!(hilbert_class_polynomial(7), to_big(&[3375, 1]));
+    assert_eq!(hilbert_class_polynomial(8), to_big(&[-8000, 1]));
+    assert_eq!(
+        hilbert_class_polynomial(163),
+        to_big(&[262537412640768000, 1])
+    );
+    assert_eq!(
+        hilbert_class_polynomial(15),
+        to_big(&[-121287375, 191025, 1])
+    );
+    assert_eq!(
+        hilbert_class_polynomial(23),
+        to_big(&[12771880859375, -5151296875, 3491750, 1])
+    );
+}
+
+#[test]
+fn test_prove_prime_ecpp() {
+    use crate::certificate::verify_certificate;
+    use std::str::FromStr;
+    for value in [
+        // 2^127 - 1 and the First Prime After 10^50
+        "170141183460469231731687303715884105727",
+        "100000000000000000000000000000000000000000000000151",
+    ] {
+        let n = BigUint::from_str(value).unwrap();
+        let certificate = prove_prime_ecpp(&n).unwrap();
+        assert!(verify_certificate(&n, &certificate), "{}", value);
+    }
+    // Carmichael Number 1590231231043178376951698401
+    let carmichael = BigUint::from_str("1590231231043178376951698401").unwrap();
+    assert_eq!(
+        prove_prime_ecpp(&carmichael),
+        Err(EcppError::Composite(carmichael))
+    );
+    // Without Discriminants the Search Fails Instead of Giving Up Silently
+    let n = BigUint::from_str("170141183460469231731687303715884105727").unwrap();
+    assert_eq!(
+        prove_prime_ecpp_with(&n, &[]),
+        Err(EcppError::NoDiscriminant(n))
+    );
+}
+
+#[test]
+#[cfg_attr(debug_assertions, ignore)]
+fn test_prove_prime_ecpp_1024bits() {
+    use crate::certificate::verify_certificate;
+    // 2^1024 - 105, the Largest 1024-bit Prime
+    let n = (BigUint::one() << 1024) - 105u32;
+    let certificate = prove_prime_ecpp(&n).unwrap();
+    assert!(verify_certificate(&n, &certificate));
+}
+
+#[test]
+fn test_verify_ecpp_step_rejects_forgeries() {
+    use std::str::FromStr;
+    let n = BigUint::from_str("100000000000000000000000000000000000000000000000151").unwrap();
+    let step = match prove_prime_ecpp(&n).unwrap() {
+        Certificate::Ecpp { step, .. } => step,
+        _ => panic!("expected an ECPP certificate"),
+    };
+    assert!(verify_ecpp_step(&step));
+    // Point Off the Curve
+    let mut forged = step.clone();
+    forged.y = (&forged.y + 1u32) % &n;
+    assert!(!verify_ecpp_step(&forged));
+    // q Too Small
+    let mut forged = step.clone();
+    forged.q = BigUint::from(2u32);
+    assert!(!verify_ecpp_step(&forged));
+    // Wrong Order
+    let mut forged = step;
+    forged.m += &forged.q;
+    assert!(!verify_ecpp_step(&forged));
+}
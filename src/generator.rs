@@ -0,0 +1,520 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use num::{BigUint, ToPrimitive};
+
+use crate::{
+    pipeline::Pipeline,
+    prime::{is_prime_bpsw, Congruence, PrimalityTest},
+    random::{Lcg, RandomGenerator},
+    rounds::miller_rabin_rounds,
+};
+
+/// Target error probability (as 2^-x) of the default round count
+pub const DEFAULT_SECURITY_BITS: u32 = 128;
+
+/// Widest interval whose primes are enumerated instead of sampled
+pub const RANGE_ENUMERATION_LIMIT: u64 = 1 << 16;
+
+/// Random draws per bit of the upper end before an interval is scanned
+pub const RANGE_SAMPLING_ATTEMPTS_PER_BIT: u64 = 64;
+
+/// Reasons a prime could not be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerationError {
+    /// The lower end is above the upper end
+    InvalidRange(BigUint, BigUint),
+    /// The interval contains no prime
+    NoPrimeInRange(BigUint, BigUint),
+    /// The requested bit length is not supported
+    InvalidBitLength(usize),
+    /// A seeded construction ran out of candidates for its seed
+    SeedExhausted,
+    /// Fewer primes than requested meet the constraints
+    NotEnoughPrimes(usize),
+    /// The search was cancelled through its `CancellationToken`
+    Cancelled,
+    /// The search passed its deadline
+    DeadlineExceeded,
+    /// The search tried its maximum number of candidates
+    CandidateLimitReached(u64),
+    /// The residue class (residue, modulus) holds at most one prime
+    InvalidCongruence(BigUint, BigUint),
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerationError::InvalidRange(lo, hi) => write!(f, "Invalid range [{}, {}]", lo, hi),
+            GenerationError::NoPrimeInRange(lo, hi) => write!(f, "No prime in [{}, {}]", lo, hi),
+            GenerationError::InvalidBitLength(bits) => write!(f, "Invalid bit length {}", bits),
+            GenerationError::SeedExhausted => write!(f, "The seed yields no prime"),
+            GenerationError::Cancelled => write!(f, "The search was cancelled"),
+            GenerationError::DeadlineExceeded => write!(f, "The search passed its deadline"),
+            GenerationError::CandidateLimitReached(count) => {
+                write!(f, "No prime among {} candidates", count)
+            }
+            GenerationError::NotEnoughPrimes(count) => {
+                write!(f, "Fewer than {} primes meet the constraints", count)
+            }
+            GenerationError::InvalidCongruence(residue, modulus) => {
+                write!(f, "The class {} mod {} holds at most one prime", residue, modulus)
+            }
+        }
+    }
+}
+
+/// Shared flag to stop a running search from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Stops every search holding a clone of this token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// # Search Limits
+/// Bounds on a prime search: a cancellation token, a deadline and a maximum
+/// number of candidates. The default has none of them.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    token: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    max_candidates: Option<u64>,
+}
+
+impl Limits {
+    /// No limits
+    pub fn none() -> Limits {
+        Limits::default()
+    }
+
+    /// Stops when `token` is cancelled
+    pub fn token(mut self, token: CancellationToken) -> Limits {
+        self.token = Some(token);
+        self
+    }
+
+    /// Stops at `deadline`
+    pub fn deadline(mut self, deadline: Instant) -> Limits {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stops `timeout` from now
+    pub fn timeout(self, timeout: Duration) -> Limits {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Stops after `max_candidates` candidates
+    pub fn max_candidates(mut self, max_candidates: u64) -> Limits {
+        self.max_candidates = Some(max_candidates);
+        self
+    }
+
+    /// Called before testing a candidate, with the number already tried
+    pub fn check(&self, candidates: u64) -> Result<(), GenerationError> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return Err(GenerationError::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(GenerationError::DeadlineExceeded);
+        }
+        match self.max_candidates {
+            Some(max_candidates) if candidates >= max_candidates => {
+                Err(GenerationError::CandidateLimitReached(max_candidates))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Extra condition on the generated primes
+type Predicate = Box<dyn Fn(&BigUint) -> bool + Send>;
+
+/// # Prime Generator
+/// Builder for random primes of an exact bit length. Every candidate has its
+/// top bit (and optionally the next one) and its low bit set, so the result
+/// is odd and has exactly `bits` bits. With more than one thread, a batch of
+/// candidates is drawn and tested concurrently. Candidates can be restricted
+/// to a residue class and to an arbitrary predicate before being tested.
+///
+/// Defaults: a time seeded `Lcg`, the default `Pipeline` with Miller-Rabin
+/// rounds from `miller_rabin_rounds(bits, DEFAULT_SECURITY_BITS)`, a single
+/// thread and no `Limits`.
+pub struct PrimeGenerator {
+    bits: usize,
+    rng: Box<dyn RandomGenerator>,
+    test: Box<dyn PrimalityTest>,
+    rounds: usize,
+    threads: usize,
+    top_two_bits: bool,
+    congruence: Option<Congruence>,
+    predicate: Option<Predicate>,
+    limits: Limits,
+}
+
+impl PrimeGenerator {
+    /// A generator of `bits`-bit primes (`generate` fails below 2 bits)
+    pub fn new(bits: usize) -> PrimeGenerator {
+        PrimeGenerator {
+            bits,
+            rng: Box::new(Lcg::from_time()),
+            test: Box::new(Pipeline::default()),
+            rounds: miller_rabin_rounds(bits.max(2), DEFAULT_SECURITY_BITS).rounds,
+            threads: 1,
+            top_two_bits: false,
+            congruence: None,
+            predicate: None,
+            limits: Limits::none(),
+        }
+    }
+
+    /// Sets the source of random candidates
+    pub fn rng<R: RandomGenerator + 'static>(mut self, rng: R) -> PrimeGenerator {
+        self.rng = Box::new(rng);
+        self
+    }
+
+    /// Sets the primality test
+    pub fn test<T: PrimalityTest + 'static>(mut self, test: T) -> PrimeGenerator {
+        self.test = Box::new(test);
+        self
+    }
+
+    /// Sets the rounds passed to the primality test
+    pub fn rounds(mut self, rounds: usize) -> PrimeGenerator {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the number of candidates tested concurrently
+    pub fn threads(mut self, threads: usize) -> PrimeGenerator {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Also sets the second highest bit, so the product of two such primes has
+    /// exactly twice as many bits
+    pub fn top_two_bits(mut self, top_two_bits: bool) -> PrimeGenerator {
+        self.top_two_bits = top_two_bits;
+        self
+    }
+
+    /// Restricts the primes to a residue class
+    pub fn congruence(mut self, congruence: Congruence) -> PrimeGenerator {
+        self.congruence = Some(congruence);
+        self
+    }
+
+    /// Only accepts primes satisfying `predicate`
+    pub fn predicate<P: Fn(&BigUint) -> bool + Send + 'static>(
+        mut self,
+        predicate: P,
+    ) -> PrimeGenerator {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// Draws a random odd candidate of exactly `bits` bits, `None` if it misses
+    /// the constraints
+    fn candidate(&mut self) -> Option<BigUint> {
+        let mut candidate = self.rng.gen_bits(self.bits);
+        candidate.set_bit(self.bits as u64 - 1, true);
+        if self.top_two_bits {
+            candidate.set_bit(self.bits as u64 - 2, true);
+        }
+        candidate.set_bit(0, true);
+        // Apply the Constraints
+        if let Some(congruence) = &self.congruence {
+            candidate = congruence.apply(&candidate);
+            let top_bits = candidate.clone() >> (self.bits - 2);
+            if candidate.bits() as usize != self.bits
+                || (self.top_two_bits && top_bits != BigUint::from(3u32))
+            {
+                return None;
+            }
+        }
+        if self
+            .predicate
+            .as_ref()
+            .is_some_and(|predicate| !predicate(&candidate))
+        {
+            return None;
+        }
+        Some(candidate)
+    }
+
+    /// Limits the search with a cancellation token, a deadline and a
+    /// maximum number of candidates
+    pub fn limits(mut self, limits: Limits) -> PrimeGenerator {
+        self.limits = limits;
+        self
+    }
+
+    /// Generates a prime, or fails on a bit length below 2, once a limit is
+    /// hit or if the congruence provably holds no prime of the bit length
+    pub fn generate(&mut self) -> Result<BigUint, GenerationError> {
+        if self.bits < 2 {
+            return Err(GenerationError::InvalidBitLength(self.bits));
+        }
+        if let Some(congruence) = &self.congruence {
+            let high = BigUint::from(1u32) << self.bits;
+            let low = if self.top_two_bits {
+                BigUint::from(3u32) << (self.bits - 2)
+            } else {
+                &high >> 1
+            };
+            congruence.check_range(&low, &high)?;
+        }
+        let mut candidates_tried: u64 = 0;
+        loop {
+            // Draw a Batch (One Candidate per Thread)
+            let mut candidates: Vec<BigUint> = Vec::new();
+            while candidates.len() < self.threads {
+                self.limits.check(candidates_tried)?;
+                candidates_tried += 1;
+                if let Some(candidate) = self.candidate() {
+                    candidates.push(candidate);
+                }
+            }
+            if self.threads == 1 {
+                if self.test.is_prime(&candidates[0], self.rounds) {
+                    return Ok(candidates.swap_remove(0));
+                }
+                continue;
+            }
+            // Test the Batch Concurrently, Keep the First Prime
+            let (test, rounds) = (&self.test, self.rounds);
+            let found = thread::scope(|scope| {
+                let handles: Vec<_> = candidates
+                    .iter()
+                    .map(|candidate| scope.spawn(move || test.is_prime(candidate, rounds)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .position(|is_prime| is_prime)
+            });
+            if let Some(index) = found {
+                return Ok(candidates.swap_remove(index));
+            }
+        }
+    }
+}
+
+/// # Prime in an Interval
+/// Returns a prime chosen uniformly among the primes in [`lo`, `hi`]. Narrow
+/// intervals (at most `RANGE_ENUMERATION_LIMIT` values) are enumerated, so an
+/// interval without primes is reported as an error. Wider ones are rejection
+/// sampled: uniform values are drawn until one passes BPSW. About ln(`hi`)
+/// draws are expected, so after `RANGE_SAMPLING_ATTEMPTS_PER_BIT` draws per
+/// bit of `hi` the interval is scanned once from a random point instead,
+/// which also reports an interval without primes as an error.
+///
+/// ## Arguments
+/// * `lo` - The lower end (inclusive)
+/// * `hi` - The upper end (inclusive)
+/// * `rng` - The source of randomness
+pub fn gen_prime_in_range<R: RandomGenerator + ?Sized>(
+    lo: &BigUint,
+    hi: &BigUint,
+    rng: &mut R,
+) -> Result<BigUint, GenerationError> {
+    if lo > hi {
+        return Err(GenerationError::InvalidRange(lo.clone(), hi.clone()));
+    }
+    let width = hi - lo + 1u32;
+    if width <= BigUint::from(RANGE_ENUMERATION_LIMIT) {
+        // Enumerate and Pick One
+        let mut primes: Vec<BigUint> = Vec::new();
+        let mut value = lo.clone();
+        while value <= *hi {
+            if is_prime_bpsw(&value) {
+                primes.push(value.clone());
+            }
+            value += 1u32;
+        }
+        if primes.is_empty() {
+            return Err(GenerationError::NoPrimeInRange(lo.clone(), hi.clone()));
+        }
+        let index = rng.gen_below(&BigUint::from(primes.len()));
+        return Ok(primes.swap_remove(index.to_usize().unwrap()));
+    }
+    sample_prime_in_range(lo, hi, rng, RANGE_SAMPLING_ATTEMPTS_PER_BIT * hi.bits())
+}
+
+/// Rejection samples up to `attempts` values of [`lo`, `hi`], then scans the
+/// whole interval from a random point, wrapping around at `hi`
+fn sample_prime_in_range<R: RandomGenerator + ?Sized>(
+    lo: &BigUint,
+    hi: &BigUint,
+    rng: &mut R,
+    attempts: u64,
+) -> Result<BigUint, GenerationError> {
+    let width = hi - lo + 1u32;
+    for _ in 0..attempts {
+        let candidate = lo + rng.gen_below(&width);
+        if is_prime_bpsw(&candidate) {
+            return Ok(candidate);
+        }
+    }
+    // Scan Once Around the Interval
+    let start = lo + rng.gen_below(&width);
+    let mut candidate = start.clone();
+    loop {
+        if is_prime_bpsw(&candidate) {
+            return Ok(candidate);
+        }
+        candidate = if candidate == *hi {
+            lo.clone()
+        } else {
+            candidate + 1u32
+        };
+        if candidate == start {
+            return Err(GenerationError::NoPrimeInRange(lo.clone(), hi.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_prime_generator() {
+    use crate::prime::{is_prime_bpsw, Bpsw};
+    use crate::random::BlumBlumShub;
+    for bits in [2, 3, 8, 64, 127, 256] {
+        let prime = PrimeGenerator::new(bits)
+            .rng(Lcg::new(BigUint::from(bits)))
+            .generate()
+            .unwrap();
+        assert_eq!(prime.bits(), bits as u64);
+        assert!(is_prime_bpsw(&prime), "{}", prime);
+    }
+    let bbs = BlumBlumShub::new(
+        BigUint::from(30000000091u64),
+        BigUint::from(40000000003u64),
+        BigUint::from(4882516701u64),
+    )
+    .unwrap();
+    let prime = PrimeGenerator::new(128)
+        .rng(bbs)
+        .test(Bpsw)
+        .top_two_bits(true)
+        .generate()
+        .unwrap();
+    assert_eq!(prime >> 126u32, BigUint::from(3u32));
+    let prime = PrimeGenerator::new(64)
+        .rng(Lcg::new(BigUint::from(5u32)))
+        .congruence(Congruence::ntt(16))
+        .predicate(|p: &BigUint| p % 3u32 == BigUint::from(2u32))
+        .top_two_bits(true)
+        .generate()
+        .unwrap();
+    assert!(is_prime_bpsw(&prime) && prime.bits() == 64 && prime.bit(62));
+    assert_eq!(prime % (3u32 << 16), BigUint::from(65537u32));
+    let prime = PrimeGenerator::new(256)
+        .test(Pipeline::standard(5))
+        .threads(4)
+        .generate()
+        .unwrap();
+    assert_eq!(prime.bits(), 256);
+    assert!(is_prime_bpsw(&prime));
+    // Constraints That Can Not Hold Stop at the Limit
+    let result = PrimeGenerator::new(64)
+        .predicate(|_: &BigUint| false)
+        .limits(Limits::none().max_candidates(100))
+        .generate();
+    assert_eq!(result, Err(GenerationError::CandidateLimitReached(100)));
+    assert_eq!(
+        PrimeGenerator::new(1).generate(),
+        Err(GenerationError::InvalidBitLength(1))
+    );
+    // A Class Without a Prime of the Bit Length Fails Up Front
+    let result = PrimeGenerator::new(16)
+        .congruence(Congruence::ntt(16))
+        .generate();
+    let (low, high) = (BigUint::from(1u32 << 15), BigUint::from(u16::MAX));
+    assert_eq!(result, Err(GenerationError::NoPrimeInRange(low, high)));
+    // 3 · 2^62 + 1 Is the Only Candidate With the Top Two Bits, and Composite
+    let result = PrimeGenerator::new(64)
+        .congruence(Congruence::ntt(62))
+        .top_two_bits(true)
+        .generate();
+    let low = BigUint::from(3u64 << 62);
+    let high = BigUint::from(u64::MAX);
+    assert_eq!(result, Err(GenerationError::NoPrimeInRange(low, high)));
+}
+
+#[test]
+fn test_gen_prime_in_range() {
+    let mut rng = Lcg::new(BigUint::from(7u32));
+    // [√2 · 2^127, 2^128)
+    let bits = 128u32;
+    let lo = (BigUint::from(1u32) << (2 * bits - 1)).sqrt() + 1u32;
+    let hi = (BigUint::from(1u32) << bits) - 1u32;
+    for _ in 0..4 {
+        let prime = gen_prime_in_range(&lo, &hi, &mut rng).unwrap();
+        assert!(prime >= lo && prime <= hi && is_prime_bpsw(&prime));
+    }
+    // Narrow Intervals, Every Prime is Reached
+    let (lo, hi) = (BigUint::from(90u32), BigUint::from(110u32));
+    let mut seen: Vec<BigUint> = (0..200)
+        .map(|_| gen_prime_in_range(&lo, &hi, &mut rng).unwrap())
+        .collect();
+    seen.sort();
+    seen.dedup();
+    let expected: Vec<BigUint> = [97u32, 101, 103, 107, 109]
+        .iter()
+        .map(|&p| BigUint::from(p))
+        .collect();
+    assert_eq!(seen, expected);
+    // Errors
+    let (lo, hi) = (BigUint::from(114u32), BigUint::from(126u32));
+    assert_eq!(
+        gen_prime_in_range(&lo, &hi, &mut rng),
+        Err(GenerationError::NoPrimeInRange(lo.clone(), hi.clone()))
+    );
+    assert_eq!(
+        gen_prime_in_range(&hi, &lo, &mut rng),
+        Err(GenerationError::InvalidRange(hi.clone(), lo.clone()))
+    );
+    assert_eq!(
+        gen_prime_in_range(&BigUint::from(2u32), &BigUint::from(2u32), &mut rng),
+        Ok(BigUint::from(2u32))
+    );
+    // The Scan After the Sampling Attempts Finds the Primes, or Fails
+    for _ in 0..20 {
+        let prime =
+            sample_prime_in_range(&BigUint::from(90u32), &BigUint::from(100u32), &mut rng, 0);
+        assert_eq!(prime, Ok(BigUint::from(97u32)));
+    }
+    assert_eq!(
+        sample_prime_in_range(&lo, &hi, &mut rng, 0),
+        Err(GenerationError::NoPrimeInRange(lo.clone(), hi.clone()))
+    );
+}
@@ -7,37 +7,60 @@ use std::{
 use num::{BigUint, FromPrimitive};
 
 use crate::prime::{
-    gen_prime_number_lcg_fermat, gen_prime_number_lcg_fermat_parallel, gen_prime_number_lcg_miller,
-    is_prime_fermat,
+    gen_prime_number_lcg, gen_prime_number_lcg_fermat, gen_prime_number_lcg_fermat_parallel,
+    gen_prime_number_lcg_miller, is_prime_fermat,
 };
+use crate::certificate::verify_certificate;
+use crate::ecpp::prove_prime_ecpp;
+use crate::generator::Limits;
+use crate::pipeline::Pipeline;
+use crate::sieve::{Atkin, Eratosthenes, Sieve, Wheel30};
+pub mod certificate;
+pub mod counting;
+pub mod ecpp;
+pub mod generator;
+pub mod mersenne;
+pub mod pipeline;
 pub mod prime;
+pub mod provable;
 pub mod random;
+pub mod rounds;
+pub mod safe;
+pub mod search;
+pub mod seeded;
+pub mod sieve;
+pub mod special;
+pub mod stream;
+pub mod strong;
 
 fn main() {
     const BENCH_BBS: bool = false;
     const BENCH_LCG: bool = false;
     const BENCH_PRIME_LCG_MILLER: bool = true;
     const BENCH_PRIME_LCG_FERMAT: bool = true;
+    const BENCH_SIEVE: bool = false;
+    const BENCH_ECPP: bool = false;
+    const BENCH_PIPELINE: bool = false;
 
     // Generate Prime
     // let child = thread::spawn(move || {
     // let time = Instant::now();
     // let now = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
-    // let prime = gen_prime_number_lcg_fermat(4096, 200, now);
+    // let prime = gen_prime_number_lcg_fermat(4096, 200, now, &Limits::none());
     // let time = time.elapsed();
     // println!("[Fermat] Generated Prime: {} \nin {}.{}s", prime, time.as_secs(), time.subsec_millis());
     // });
     // let child2 = thread::spawn(move || {
     // let time = Instant::now();
     // let now = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
-    // let prime = gen_prime_number_lcg_miller(4096, 200, now);
+    // let prime = gen_prime_number_lcg_miller(4096, 200, now, &Limits::none());
     // let time = time.elapsed();
     // println!("[MILLER] Generated Prime: {} in \n{}.{}s - Minimum Bits: {}", prime, time.as_secs(), time.subsec_millis(), prime.bits());
     // });
     // let child3 = thread::spawn(move || {
     // let time = Instant::now();
     // let now = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
-    // let prime = gen_prime_number_lcg_fermat_parallel(4096, 200, now, 4);
+    // let prime = gen_prime_number_lcg_fermat_parallel(4096, 200, now, 4, &Limits::none());
     // let time = time.elapsed();
     // println!("[FermatP] Generated Prime: {} in \n{}.{}s", prime, time.as_secs(), time.subsec_millis());
     // });
@@ -124,7 +147,7 @@ fn main() {
                         .unwrap()
                         .as_millis(),
                 );
-                gen_prime_number_lcg_miller(size, 200, now);
+                gen_prime_number_lcg_miller(size, 200, now, &Limits::none()).unwrap();
                 size_time += time_loop_init.elapsed();
             }
             let elapsed = initial_time.elapsed();
@@ -153,7 +176,7 @@ fn main() {
                         .unwrap()
                         .as_millis(),
                 );
-                gen_prime_number_lcg_fermat(size, 200, now);
+                gen_prime_number_lcg_fermat(size, 200, now, &Limits::none()).unwrap();
                 size_time += time_loop_init.elapsed();
             }
             let elapsed = initial_time.elapsed();
@@ -168,4 +191,83 @@ fn main() {
             );
         }
     }
+    if BENCH_SIEVE {
+        println!("Sieve Benchmark:");
+        let sieves: Vec<Box<dyn Sieve>> =
+            vec![Box::new(Eratosthenes), Box::new(Atkin), Box::new(Wheel30)];
+        let limits: Vec<u64> = vec![1_000_000, 10_000_000, 100_000_000, 1_000_000_000];
+        for sieve in sieves {
+            for &limit in limits.iter() {
+                let initial_time = Instant::now();
+                let primes = sieve.primes_up_to(limit);
+                let elapsed = initial_time.elapsed();
+                println!(
+                    "[SIEVE] [{}] [{}]\tElapsed: {}.{}s   \t- Primes: {}",
+                    sieve.name(),
+                    limit,
+                    elapsed.as_secs(),
+                    elapsed.subsec_millis(),
+                    primes.len()
+                );
+            }
+        }
+    }
+    if BENCH_ECPP {
+        println!("Elliptic Curve Primality Proving Benchmark:");
+        let sizes: Vec<usize> = vec![128, 256, 512, 768, 1024];
+        for size in sizes {
+            let now = BigUint::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis(),
+            );
+            let prime = gen_prime_number_lcg_miller(size, 200, now, &Limits::none()).unwrap();
+            let initial_time = Instant::now();
+            let certificate = prove_prime_ecpp(&prime);
+            let elapsed = initial_time.elapsed();
+            let verified = certificate
+                .map(|certificate| verify_certificate(&prime, &certificate))
+                .unwrap_or(false);
+            println!(
+                "[ECPP] [{} B]\tElapsed: {}.{}s   \t- Verified: {}",
+                size,
+                elapsed.as_secs(),
+                elapsed.subsec_millis(),
+                verified
+            );
+        }
+    }
+    if BENCH_PIPELINE {
+        println!("Primality Pipeline Benchmark:");
+        let sizes: Vec<usize> = vec![512, 1024, 2048, 4096];
+        for size in sizes {
+            let pipeline = Pipeline::standard(40);
+            let now = BigUint::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis(),
+            );
+            let initial_time = Instant::now();
+            gen_prime_number_lcg(&pipeline, size, 0, now, &Limits::none()).unwrap();
+            let elapsed = initial_time.elapsed();
+            println!(
+                "[PIPELINE] [{} B]\tElapsed: {}.{}s",
+                size,
+                elapsed.as_secs(),
+                elapsed.subsec_millis()
+            );
+            for stats in pipeline.stats() {
+                println!(
+                    "\t[{}]\tTested: {}\t- Rejected: {}\t- Elapsed: {}.{}s",
+                    stats.name,
+                    stats.tested,
+                    stats.rejected,
+                    stats.elapsed.as_secs(),
+                    stats.elapsed.subsec_millis()
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,118 @@
+use num::{BigUint, One};
+
+use crate::{
+    prime::{is_prime_u64, pow_mod_u64},
+    sieve::primes_in_range,
+};
+
+/// Largest multiplier k tried when trial factoring with q = 2kp + 1
+pub const TRIAL_FACTOR_K_LIMIT: u64 = 1 << 16;
+
+/// Reduces x modulo 2^p - 1 with shifts and adds: x = hi * 2^p + lo ≡ hi + lo
+fn reduce_mersenne(mut x: BigUint, p: u64, mersenne: &BigUint) -> BigUint {
+    while x.bits() > p {
+        x = (&x & mersenne) + (x >> p);
+    }
+    if &x == mersenne {
+        BigUint::from(0u32)
+    } else {
+        x
+    }
+}
+
+/// # Lucas-Lehmer Test
+/// Decides whether 2^p - 1 is prime: with s_0 = 4 and s_(i+1) = s_i^2 - 2,
+/// M_p is prime iff s_(p-2) ≡ 0 (mod M_p). Composite exponents are rejected
+/// directly since M_ab is divisible by M_a.
+///
+/// ## Arguments
+/// * `p` - The exponent
+pub fn is_mersenne_prime(p: u64) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !is_prime_u64(p) {
+        return false;
+    }
+    let mersenne = (BigUint::one() << p) - 1u32;
+    let two = BigUint::from(2u32);
+    let mut s = BigUint::from(4u32);
+    for _ in 0..p - 2 {
+        s = reduce_mersenne(&s * &s, p, &mersenne);
+        // Subtract 2 Without Going Negative
+        if s < two {
+            s += &mersenne;
+        }
+        s -= &two;
+    }
+    s == BigUint::from(0u32)
+}
+
+/// # Trial Factoring of Mersenne Numbers
+/// Looks for a factor of 2^p - 1 (p an odd prime) among q = 2kp + 1 with
+/// k ≤ `max_k`. Only q ≡ ±1 (mod 8) can divide M_p, and q divides it iff
+/// 2^p ≡ 1 (mod q).
+///
+/// ## Arguments
+/// * `p` - The odd prime exponent
+/// * `max_k` - The largest multiplier tried
+pub fn trial_factor_mersenne(p: u64, max_k: u64) -> Option<u64> {
+    for k in 1..=max_k {
+        let q = k.checked_mul(2 * p)?.checked_add(1)?;
+        // A Proper Factor is Below M_p
+        if p < 64 && q >= (1u64 << p) - 1 {
+            return None;
+        }
+        if q % 8 != 1 && q % 8 != 7 {
+            continue;
+        }
+        if pow_mod_u64(2, p, q) == 1 {
+            return Some(q);
+        }
+    }
+    None
+}
+
+/// # Mersenne Prime Search
+/// Scans the prime exponents in [from, to] and returns those p for which
+/// 2^p - 1 is prime. Each candidate is trial factored first, Lucas-Lehmer
+/// only runs on the survivors.
+///
+/// ## Arguments
+/// * `from` - The first exponent (inclusive)
+/// * `to` - The last exponent (inclusive)
+pub fn search_mersenne_primes(from: u64, to: u64) -> Vec<u64> {
+    primes_in_range(from, to)
+        .into_iter()
+        .filter(|&p| p == 2 || trial_factor_mersenne(p, TRIAL_FACTOR_K_LIMIT).is_none())
+        .filter(|&p| is_mersenne_prime(p))
+        .collect()
+}
+
+#[test]
+fn test_is_mersenne_prime() {
+    for p in [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521] {
+        assert!(is_mersenne_prime(p), "{}", p);
+    }
+    for p in [1, 4, 9, 11, 23, 29, 37, 67, 257] {
+        assert!(!is_mersenne_prime(p), "{}", p);
+    }
+}
+
+#[test]
+fn test_trial_factor_mersenne() {
+    // M11 = 23 * 89, M29 = 233 * 1103 * 2089, M37 = 223 * 616318177
+    assert_eq!(trial_factor_mersenne(11, 10), Some(23));
+    assert_eq!(trial_factor_mersenne(29, 10), Some(233));
+    assert_eq!(trial_factor_mersenne(37, 10), Some(223));
+    assert_eq!(trial_factor_mersenne(31, 1 << 12), None);
+    assert_eq!(trial_factor_mersenne(3, 10), None);
+}
+
+#[test]
+fn test_search_mersenne_primes() {
+    assert_eq!(
+        search_mersenne_primes(1, 1300),
+        vec![2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279]
+    );
+}
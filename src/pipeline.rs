@@ -0,0 +1,257 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use num::{BigUint, Integer};
+
+use crate::prime::{
+    is_strong_lucas_probable_prime, small_odd_primes, MillerRabin, PrimalityTest,
+    SMALL_PRIMES_BOUND,
+};
+
+/// Rejects candidates with an odd prime factor below the bound (or even)
+#[derive(Debug, Clone)]
+pub struct TrialDivision {
+    primes: Vec<u32>,
+}
+
+impl TrialDivision {
+    /// Trial division by the odd primes below `bound`
+    pub fn new(bound: u32) -> TrialDivision {
+        TrialDivision {
+            primes: small_odd_primes(bound),
+        }
+    }
+}
+
+/// Fermat test to the single base 2
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FermatBase2;
+
+/// Strong Lucas probable prime test with Selfridge's parameters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrongLucas;
+
+impl PrimalityTest for TrialDivision {
+    fn name(&self) -> &'static str {
+        "Trial Division"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, _rounds: usize) -> bool {
+        if maybe_prime.is_even() {
+            return *maybe_prime == BigUint::from(2u32);
+        }
+        self.primes.iter().all(|&prime| {
+            maybe_prime % prime != BigUint::from(0u32) || *maybe_prime == BigUint::from(prime)
+        }) && *maybe_prime > BigUint::from(1u32)
+    }
+}
+
+impl PrimalityTest for FermatBase2 {
+    fn name(&self) -> &'static str {
+        "Fermat (Base 2)"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, _rounds: usize) -> bool {
+        if *maybe_prime < BigUint::from(4u32) {
+            return *maybe_prime > BigUint::from(1u32);
+        }
+        BigUint::from(2u32).modpow(&(maybe_prime - 1u32), maybe_prime) == BigUint::from(1u32)
+    }
+}
+
+impl PrimalityTest for StrongLucas {
+    fn name(&self) -> &'static str {
+        "Strong Lucas"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, _rounds: usize) -> bool {
+        if *maybe_prime < BigUint::from(4u32) || maybe_prime.is_even() {
+            return *maybe_prime == BigUint::from(2u32) || *maybe_prime == BigUint::from(3u32);
+        }
+        // Squares Have No Suitable D
+        let root = maybe_prime.sqrt();
+        &root * &root != *maybe_prime && is_strong_lucas_probable_prime(maybe_prime)
+    }
+}
+
+/// Counters of a single pipeline stage
+#[derive(Debug, Clone)]
+pub struct StageStats {
+    /// Name of the stage's test
+    pub name: &'static str,
+    /// Candidates that reached the stage
+    pub tested: u64,
+    /// Candidates the stage proved composite
+    pub rejected: u64,
+    /// Total time spent in the stage
+    pub elapsed: Duration,
+}
+
+/// # Primality Pipeline
+/// Chains primality tests from cheapest to most expensive. A candidate is
+/// reported prime only if every stage accepts it, and each stage records how
+/// many candidates it saw, how many it rejected and how long it took. The
+/// pipeline is itself a `PrimalityTest`, so it plugs into the generators.
+///
+/// The default pipeline is the standard one with Miller-Rabin running the
+/// rounds passed to `is_prime`. It is the default test of `PrimeGenerator`
+/// and `PrimeStream`.
+pub struct Pipeline {
+    /// Each test with its rounds, `None` for the rounds passed to `is_prime`
+    stages: Vec<(Box<dyn PrimalityTest>, Option<usize>)>,
+    stats: Mutex<Vec<StageStats>>,
+}
+
+impl Pipeline {
+    /// An empty pipeline (accepts everything)
+    pub fn new() -> Pipeline {
+        Pipeline {
+            stages: Vec::new(),
+            stats: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Trial division → base-2 Fermat → `rounds` of Miller-Rabin → strong Lucas
+    pub fn standard(rounds: usize) -> Pipeline {
+        Pipeline::new()
+            .stage(TrialDivision::new(SMALL_PRIMES_BOUND), 1)
+            .stage(FermatBase2, 1)
+            .stage(MillerRabin, rounds)
+            .stage(StrongLucas, 1)
+    }
+
+    /// Appends a stage running `test` with `rounds`
+    pub fn stage<T: PrimalityTest + 'static>(self, test: T, rounds: usize) -> Pipeline {
+        self.push_stage(test, Some(rounds))
+    }
+
+    fn push_stage<T: PrimalityTest + 'static>(
+        mut self,
+        test: T,
+        rounds: Option<usize>,
+    ) -> Pipeline {
+        self.stats.get_mut().unwrap().push(StageStats {
+            name: test.name(),
+            tested: 0,
+            rejected: 0,
+            elapsed: Duration::new(0, 0),
+        });
+        self.stages.push((Box::new(test), rounds));
+        self
+    }
+
+    /// A snapshot of the counters of every stage, in order
+    pub fn stats(&self) -> Vec<StageStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Clears the counters
+    pub fn reset_stats(&self) {
+        for stats in self.stats.lock().unwrap().iter_mut() {
+            stats.tested = 0;
+            stats.rejected = 0;
+            stats.elapsed = Duration::new(0, 0);
+        }
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Pipeline {
+        Pipeline::new()
+            .stage(TrialDivision::new(SMALL_PRIMES_BOUND), 1)
+            .stage(FermatBase2, 1)
+            .push_stage(MillerRabin, None)
+            .stage(StrongLucas, 1)
+    }
+}
+
+impl PrimalityTest for Pipeline {
+    fn name(&self) -> &'static str {
+        "Pipeline"
+    }
+
+    /// Runs the stages in order; `rounds` only reaches the stages without
+    /// rounds of their own
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        for (index, (test, stage_rounds)) in self.stages.iter().enumerate() {
+            let initial_time = Instant::now();
+            let accepted = test.is_prime(maybe_prime, stage_rounds.unwrap_or(rounds));
+            // Only Lock to Update, so Threads can Share the Pipeline
+            let mut stats = self.stats.lock().unwrap();
+            stats[index].elapsed += initial_time.elapsed();
+            stats[index].tested += 1;
+            if !accepted {
+                stats[index].rejected += 1;
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn test_pipeline() {
+    use crate::generator::Limits;
+    use crate::prime::{gen_prime_number_lcg, is_prime_bpsw};
+    use crate::sieve::primes_in_range;
+    let pipeline = Pipeline::standard(5);
+    // Agrees With the Sieve
+    let primes = primes_in_range(1_000_000_000, 1_000_002_000);
+    for value in 1_000_000_000..1_000_002_000u64 {
+        let expected = primes.binary_search(&value).is_ok();
+        assert_eq!(
+            pipeline.is_prime(&BigUint::from(value), 0),
+            expected,
+            "{}",
+            value
+        );
+    }
+    // Every Candidate Enters, Each Stage Sees the Survivors of the Previous
+    let stats = pipeline.stats();
+    assert_eq!(stats.len(), 4);
+    assert_eq!(stats[0].tested, 2000);
+    for pair in stats.windows(2) {
+        assert_eq!(pair[1].tested, pair[0].tested - pair[0].rejected);
+    }
+    assert_eq!(stats[3].tested - stats[3].rejected, primes.len() as u64);
+    // Drives a Generator
+    pipeline.reset_stats();
+    let prime =
+        gen_prime_number_lcg(&pipeline, 256, 0, BigUint::from(42u32), &Limits::none()).unwrap();
+    assert!(is_prime_bpsw(&prime));
+    assert_eq!(pipeline.stats()[3].rejected, 0);
+    assert!(pipeline.stats()[0].rejected > 0);
+}
+
+#[test]
+fn test_default_pipeline() {
+    use std::sync::Arc;
+    // Records the Rounds It Receives
+    struct Recorder(Arc<Mutex<Vec<usize>>>);
+    impl PrimalityTest for Recorder {
+        fn name(&self) -> &'static str {
+            "Recorder"
+        }
+
+        fn is_prime(&self, _maybe_prime: &BigUint, rounds: usize) -> bool {
+            self.0.lock().unwrap().push(rounds);
+            true
+        }
+    }
+    // Same Stages as the Standard Pipeline
+    let names = |pipeline: &Pipeline| -> Vec<&'static str> {
+        pipeline.stats().iter().map(|stats| stats.name).collect()
+    };
+    assert_eq!(names(&Pipeline::default()), names(&Pipeline::standard(1)));
+    assert!(Pipeline::default().is_prime(&BigUint::from(1_000_000_007u32), 5));
+    assert!(!Pipeline::default().is_prime(&BigUint::from(1_000_000_011u32), 5));
+    // Only Stages Without Rounds of Their Own Get the Caller's
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let pipeline = Pipeline::new()
+        .stage(Recorder(Arc::clone(&seen)), 3)
+        .push_stage(Recorder(Arc::clone(&seen)), None);
+    assert!(pipeline.is_prime(&BigUint::from(7u32), 9));
+    assert_eq!(*seen.lock().unwrap(), vec![3, 9]);
+}
@@ -1,10 +1,49 @@
 use std::{ops::{DivAssign, Shl}, str::FromStr, thread, time::{SystemTime, UNIX_EPOCH}};
 
-use num::{traits::Pow, BigUint, FromPrimitive, Integer};
+use num::{traits::Pow, BigUint, FromPrimitive, Integer, ToPrimitive};
 
+use crate::generator::{GenerationError, Limits, RANGE_ENUMERATION_LIMIT};
 use crate::random::gen_linear_congruential_generator;
 
+/// Draws the next Miller-Rabin base a in [2, n - 2] (n odd, n ≥ 5) from the
+/// MUSL LCG and advances the seed, so every round uses a different base
+fn miller_rabin_base(maybe_prime: &BigUint, seed: &mut BigUint) -> BigUint {
+    // Generate a Random Number
+    let random = gen_linear_congruential_generator(
+        BigUint::from_i32(2)
+            .unwrap()
+            .pow(maybe_prime.bits().next_power_of_two()),
+        BigUint::from_i64(6364136223846793005i64).unwrap(),
+        BigUint::from(1u32),
+        seed.clone(),
+    )
+    .unwrap();
+    // Update Seed
+    *seed = random.clone();
+    // Take the Base a in [2, n - 2]
+    random.mod_floor(&(maybe_prime - 3u32)) + 2u32
+}
+
 pub fn is_prime_miller_rabin(maybe_prime: BigUint, rounds: usize) -> bool {
+    // Create a Initial Seed
+    let seed = BigUint::from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis(),
+    );
+    is_prime_miller_rabin_seeded(maybe_prime, rounds, seed)
+}
+
+/// # Seeded Miller-Rabin
+/// Miller-Rabin with the bases drawn from `seed` instead of the clock, so the
+/// verdict on a composite is reproducible.
+///
+/// ## Arguments
+/// * `maybe_prime` - The number to test
+/// * `rounds` - The number of bases
+/// * `seed` - The seed of the bases
+pub fn is_prime_miller_rabin_seeded(maybe_prime: BigUint, rounds: usize, mut seed: BigUint) -> bool {
     // Create Big Numbers
     let big_one: BigUint = BigUint::from(1u32);
     let big_two: BigUint = BigUint::from(2u32);
@@ -13,7 +52,7 @@ pub fn is_prime_miller_rabin(maybe_prime: BigUint, rounds: usize) -> bool {
     if maybe_prime == big_two || maybe_prime == big_three {
         return true;
     }
-    if maybe_prime.is_even() {
+    if maybe_prime < big_two || maybe_prime.is_even() {
         return false;
     }
     // Find s = max{r in N / (2^r) % (n-1) == 0}
@@ -25,26 +64,11 @@ pub fn is_prime_miller_rabin(maybe_prime: BigUint, rounds: usize) -> bool {
         maybe_odd_part.div_assign(&big_two);
     }
     // Repeat a arbitrary number of rounds
-    let seed = BigUint::from(
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis(),
-    );
     for _ in 0..rounds {
-        // Generate a Random Number
-        let random = gen_linear_congruential_generator(
-            BigUint::from_i32(2)
-                .unwrap()
-                .pow(maybe_prime.bits().next_power_of_two()),
-            BigUint::from_i64(6364136223846793005i64).unwrap(),
-            BigUint::from(1u32),
-            seed.clone(),
-        )
-        .unwrap()
-        .clamp(big_two.clone(), &maybe_prime - &big_two);
+        // Take a New Base Every Round
+        let base = miller_rabin_base(&maybe_prime, &mut seed);
         // Compute x ← a^d mod n
-        let mut x_value = random.modpow(&maybe_odd_part, &maybe_prime);
+        let mut x_value = base.modpow(&maybe_odd_part, &maybe_prime);
         // Check Inconclusive
         if x_value == big_one || x_value == (&maybe_prime - &big_one) {
             continue;
@@ -67,30 +91,8 @@ pub fn is_prime_miller_rabin(maybe_prime: BigUint, rounds: usize) -> bool {
     return true;
 }
 
-pub fn gen_prime_number_lcg_miller(bit_length: usize, strongness: usize, seed: BigUint) -> BigUint {
-    // Try Generate a Value
-    let mut current_random = seed.clone();
-    loop {
-        // Generate a Random Number (With POSIX parameters)
-        let random = gen_linear_congruential_generator(
-            BigUint::from_i32(2).unwrap().pow(bit_length - 1),
-            BigUint::from(25214903917u64),
-            BigUint::from(11u64),
-            current_random.clone(),
-        );
-        // Continue if Error
-        if random.is_ok() {
-            // Check Prime
-            let mut maybe_prime: BigUint = random.unwrap() >> 1;
-            maybe_prime.set_bit(u64::from_usize(bit_length - 1).unwrap(), true);
-            maybe_prime.set_bit(0, true);
-            if is_prime_miller_rabin(maybe_prime.clone(), strongness) {
-                // Return Value
-                return maybe_prime;
-            }
-            current_random = maybe_prime;
-        }
-    }
+pub fn gen_prime_number_lcg_miller(bit_length: usize, strongness: usize, seed: BigUint, limits: &Limits) -> Result<BigUint, GenerationError> {
+    gen_prime_number_lcg(&MillerRabin, bit_length, strongness, seed, limits)
 }
 
 pub fn is_prime_fermat(maybe_prime: BigUint, rounds: usize) -> bool {
@@ -140,10 +142,10 @@ pub fn is_prime_fermat_parallel(maybe_prime: BigUint, rounds: usize, threads: us
             .as_millis(),
     );
     // Execute an arbitrary number of tests
-    let threads = (0..threads).into_iter().map(|_thread_id| {
-        let rounds_per_thread = rounds / threads;
+    let threads = (0..threads).into_iter().map(|thread_id| {
+        let rounds_per_thread = rounds.div_ceil(threads);
         let maybe_prime = maybe_prime.clone();
-        let mut seed = seed.clone();
+        let mut seed = &seed + thread_id;
         let big_one = BigUint::from_i32(1).unwrap();
         thread::spawn(move || {
             for _ in 0..rounds_per_thread {
@@ -173,17 +175,70 @@ pub fn is_prime_fermat_parallel(maybe_prime: BigUint, rounds: usize, threads: us
             return true;
         })
     });
-    // Wait Threads
+    // Wait Threads (Every Thread Must Fail to Prove it Composite)
     threads.map(|thread| {
         thread.join().unwrap()
-    }).any(|v| v)
+    }).all(|v| v)
 }
 
-pub fn gen_prime_number_lcg_fermat(bit_length: usize, strongness: usize, seed: BigUint) -> BigUint {
+pub fn gen_prime_number_lcg_fermat(bit_length: usize, strongness: usize, seed: BigUint, limits: &Limits) -> Result<BigUint, GenerationError> {
+    gen_prime_number_lcg(&Fermat, bit_length, strongness, seed, limits)
+}
+
+pub fn gen_prime_number_lcg_fermat_parallel(bit_length: usize, strongness: usize, seed: BigUint, threads: usize, limits: &Limits) -> Result<BigUint, GenerationError> {
+    gen_prime_number_lcg(&FermatParallel { threads }, bit_length, strongness, seed, limits)
+}
+
+/// Upper bound (exclusive) for the small primes used to discard candidates
+pub const SMALL_PRIMES_BOUND: u32 = 2048;
+
+/// Computes the odd primes below `bound` by trial division
+pub fn small_odd_primes(bound: u32) -> Vec<u32> {
+    let mut primes: Vec<u32> = Vec::new();
+    for value in (3..bound).step_by(2) {
+        if primes
+            .iter()
+            .take_while(|&&prime| prime * prime <= value)
+            .all(|&prime| value % prime != 0)
+        {
+            primes.push(value);
+        }
+    }
+    primes
+}
+
+/// # Incremental Prime Search
+/// Draws a single random odd start of `bit_length` bits and walks upwards in
+/// steps of 2. The residues of the start modulo the small primes are computed
+/// once, so candidates with a small factor are skipped using only machine word
+/// arithmetic. Only the survivors are tested with Miller-Rabin. If the walk
+/// overflows the requested bit length a new start is drawn. Fails on bit
+/// lengths below 2 or once one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `bit_length` - The bit length of the generated prime (MSB = 1)
+/// * `strongness` - The number of Miller-Rabin rounds
+/// * `seed` - The seed of the Linear Congruential Generator
+/// * `limits` - When to give up, counting every candidate of the walk
+pub fn gen_prime_number_incremental_miller(
+    bit_length: usize,
+    strongness: usize,
+    seed: BigUint,
+    limits: &Limits,
+) -> Result<BigUint, GenerationError> {
+    if bit_length < 2 {
+        return Err(GenerationError::InvalidBitLength(bit_length));
+    }
+    // Only use primes smaller than any candidate, so a candidate is never discarded for being one
+    let small_primes: Vec<u32> = small_odd_primes(SMALL_PRIMES_BOUND)
+        .into_iter()
+        .filter(|&prime| ((32 - prime.leading_zeros()) as usize) < bit_length)
+        .collect();
     // Try Generate a Value
-    let mut current_random = seed.clone();
+    let mut current_random = seed;
+    let mut candidates: u64 = 0;
     loop {
-        // Generate a Random Number (With POSIX parameters)
+        // Generate a Random Start (With POSIX parameters)
         let random = gen_linear_congruential_generator(
             BigUint::from_i32(2).unwrap().pow(bit_length),
             BigUint::from(25214903917u64),
@@ -191,45 +246,903 @@ pub fn gen_prime_number_lcg_fermat(bit_length: usize, strongness: usize, seed: B
             current_random.clone(),
         );
         // Continue if Error
-        if random.is_ok() {
-            // Check Prime
-            let mut maybe_prime: BigUint = random.unwrap() >> 1;
-            maybe_prime.set_bit(u64::from_usize(bit_length - 1).unwrap(), true);
-            maybe_prime.set_bit(0, true);
-            if is_prime_fermat(maybe_prime.clone(), strongness) {
-                // Return Value
-                return maybe_prime;
+        let mut start = match random {
+            Ok(random) => random,
+            Err(_) => continue,
+        };
+        current_random = start.clone();
+        start.set_bit(u64::from_usize(bit_length - 1).unwrap(), true);
+        start.set_bit(0, true);
+        // Walk From the Start, Drawing a New One Once it Leaves the Bit Length
+        let found = walk_sieved(&start, bit_length, &small_primes, |maybe_prime| {
+            if let Err(error) = limits.check(candidates) {
+                return Some(Err(error));
+            }
+            candidates += 1;
+            let maybe_prime = maybe_prime?;
+            if is_prime_miller_rabin(maybe_prime.clone(), strongness) {
+                return Some(Ok(maybe_prime.clone()));
+            }
+            None
+        });
+        if let Some(result) = found {
+            // Return Value
+            return result;
+        }
+    }
+}
+
+/// # Sieved Walk
+/// Walks upwards from the odd `start` in steps of 2 while the candidates keep
+/// `bit_length` bits. The residues of the start modulo `small_primes` are
+/// computed once and then stepped with machine word arithmetic, so `visit`
+/// gets `None` for a candidate with a small factor and the candidate
+/// otherwise. The walk stops at the first `Some` returned by `visit`.
+///
+/// ## Arguments
+/// * `start` - The odd first candidate
+/// * `bit_length` - The bit length the candidates must keep
+/// * `small_primes` - The odd primes used to discard candidates, all below `start`
+/// * `visit` - Called on every step of the walk
+pub(crate) fn walk_sieved<T, F>(start: &BigUint, bit_length: usize, small_primes: &[u32], mut visit: F) -> Option<T>
+where
+    F: FnMut(Option<&BigUint>) -> Option<T>,
+{
+    // Compute the Residues of the Start
+    let mut residues: Vec<u32> = small_primes
+        .iter()
+        .map(|&prime| (start % prime).to_u32().unwrap())
+        .collect();
+    let mut candidate = start.clone();
+    // Walk by 2 Within the Bit Length
+    while candidate.bits() as usize <= bit_length {
+        // Skip Candidates With Small Factors
+        let has_small_factor = residues.contains(&0);
+        let found = visit(if has_small_factor { None } else { Some(&candidate) });
+        if found.is_some() {
+            return found;
+        }
+        // Step the Candidate and its Residues
+        candidate += 2u32;
+        for (residue, &prime) in residues.iter_mut().zip(small_primes) {
+            *residue += 2;
+            if *residue >= prime {
+                *residue -= prime;
+            }
+        }
+    }
+    None
+}
+
+/// Computes a * b mod m without overflowing
+fn mul_mod_u64(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+/// Computes base^exp mod m without overflowing
+pub(crate) fn pow_mod_u64(mut base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut result: u64 = 1;
+    base %= modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod_u64(result, base, modulus);
+        }
+        base = mul_mod_u64(base, base, modulus);
+        exp >>= 1;
+    }
+    result
+}
+
+/// # Deterministic Miller-Rabin for Machine Integers
+/// Tests `maybe_prime` with the first 12 primes as bases. No composite below
+/// 3.3·10^24 is a strong pseudoprime to all of them, so the answer is exact
+/// for every `u64`.
+pub fn is_prime_u64(maybe_prime: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if maybe_prime < 2 {
+        return false;
+    }
+    // Trial Division by the Bases
+    for &base in BASES.iter() {
+        if maybe_prime.is_multiple_of(base) {
+            return maybe_prime == base;
+        }
+    }
+    // Write n - 1 = d * 2^s
+    let max_exp_that_divides = (maybe_prime - 1).trailing_zeros();
+    let odd_part = (maybe_prime - 1) >> max_exp_that_divides;
+    'bases: for &base in BASES.iter() {
+        let mut x_value = pow_mod_u64(base, odd_part, maybe_prime);
+        if x_value == 1 || x_value == maybe_prime - 1 {
+            continue;
+        }
+        for _ in 1..max_exp_that_divides {
+            x_value = mul_mod_u64(x_value, x_value, maybe_prime);
+            if x_value == maybe_prime - 1 {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// # Jacobi Symbol
+/// Computes (a/n) for an odd n with the binary algorithm (quadratic
+/// reciprocity). Returns 0 when gcd(a, n) > 1.
+///
+/// ## Arguments
+/// * `a` - The numerator
+/// * `n` - The odd denominator
+pub fn jacobi_symbol(a: &BigUint, n: &BigUint) -> i32 {
+    assert!(n.is_odd(), "the Jacobi symbol needs an odd denominator");
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+    while a != BigUint::from(0u32) {
+        // Pull Out the Factors of 2: (2/n) = -1 iff n ≡ 3, 5 (mod 8)
+        let twos = a.trailing_zeros().unwrap();
+        a >>= twos;
+        let n_mod_8 = (&n % 8u32).to_u32().unwrap();
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        // Reciprocity: Flip the Sign if Both are ≡ 3 (mod 4)
+        if n_mod_8 % 4 == 3 && (&a % 4u32).to_u32().unwrap() == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= &n;
+    }
+    if n == BigUint::from(1u32) {
+        result
+    } else {
+        0
+    }
+}
+
+/// Checks whether n = a^b for some a and b ≥ 2
+fn is_perfect_power(n: &BigUint) -> bool {
+    (2..n.bits() as u32 + 1).any(|b| {
+        let root = n.nth_root(b);
+        root > BigUint::from(1u32) && root.pow(b) == *n
+    })
+}
+
+/// Computes Euler's totient of a small number
+fn euler_phi(mut r: u64) -> u64 {
+    let mut phi = r;
+    let mut p = 2;
+    while p * p <= r {
+        if r.is_multiple_of(p) {
+            while r.is_multiple_of(p) {
+                r /= p;
+            }
+            phi -= phi / p;
+        }
+        p += 1;
+    }
+    if r > 1 {
+        phi -= phi / r;
+    }
+    phi
+}
+
+/// Multiplies two polynomials of (Z/nZ)[X]/(X^r - 1) with Kronecker
+/// substitution: the coefficients are packed in `slot` 32-bit digits of one
+/// integer
+fn aks_mul(a: &[BigUint], b: &[BigUint], n: &BigUint, slot: usize) -> Vec<BigUint> {
+    let pack = |poly: &[BigUint]| {
+        let mut digits = vec![0u32; poly.len() * slot];
+        for (i, coefficient) in poly.iter().enumerate() {
+            for (j, digit) in coefficient.to_u32_digits().into_iter().enumerate() {
+                digits[i * slot + j] = digit;
+            }
+        }
+        BigUint::new(digits)
+    };
+    let product = (pack(a) * pack(b)).to_u32_digits();
+    let r = a.len();
+    let mut result = vec![BigUint::from(0u32); r];
+    // X^(r + i) = X^i
+    for (i, chunk) in product.chunks(slot).enumerate() {
+        result[i % r] += BigUint::from_slice(chunk);
+    }
+    result.iter().map(|coefficient| coefficient % n).collect()
+}
+
+/// # AKS Primality Test
+/// Agrawal-Kayal-Saxena deterministic test: rejects perfect powers, finds
+/// the smallest r with ord_r(n) > log2(n)^2, checks gcd(a, n) for a ≤ r and
+/// finally the congruences (X + a)^n ≡ X^n + a in (Z/nZ)[X]/(X^r - 1) for
+/// a ≤ √φ(r) log2(n). Polynomial time, but far slower than the probabilistic
+/// tests.
+///
+/// ## Arguments
+/// * `n` - The number to test
+pub fn is_prime_aks(n: &BigUint) -> bool {
+    let one = BigUint::from(1u32);
+    if *n <= one {
+        return false;
+    }
+    if is_perfect_power(n) {
+        return false;
+    }
+    // Smallest r With ord_r(n) > log2(n)^2 (bits ≥ log2 n Keeps the Bound Safe)
+    let log = n.bits();
+    let order_bound = log * log;
+    let mut r = 2u64;
+    loop {
+        let residue = (n % r).to_u64().unwrap();
+        if residue.gcd(&r) == 1 {
+            let mut power = 1u64;
+            let has_small_order = (1..=order_bound).any(|_| {
+                power = power * residue % r;
+                power == 1
+            });
+            if !has_small_order {
+                break;
+            }
+        }
+        r += 1;
+    }
+    // Small Factors
+    for a in 2..=r {
+        let a = BigUint::from(a);
+        if &a >= n {
+            break;
+        }
+        if a.gcd(n) != one {
+            return false;
+        }
+    }
+    if *n <= BigUint::from(r) {
+        return true;
+    }
+    // (X + a)^n ≡ X^(n mod r) + a
+    let limit = ((euler_phi(r) as f64).sqrt() * log as f64) as u64;
+    let slot = (2 * n.bits() as usize + (64 - r.leading_zeros()) as usize).div_ceil(32);
+    let r = r as usize;
+    let n_mod_r = (n % r).to_usize().unwrap();
+    for a in 1..=limit {
+        let a = BigUint::from(a) % n;
+        let mut result = vec![BigUint::from(0u32); r];
+        result[0] = one.clone();
+        for bit in (0..n.bits()).rev() {
+            result = aks_mul(&result, &result, n, slot);
+            if n.bit(bit) {
+                // Multiply by X + a
+                let shifted: Vec<BigUint> = (0..r).map(|i| result[(i + r - 1) % r].clone()).collect();
+                result = shifted
+                    .iter()
+                    .zip(result.iter())
+                    .map(|(high, low)| (high + low * &a) % n)
+                    .collect();
+            }
+        }
+        let mut expected = vec![BigUint::from(0u32); r];
+        expected[n_mod_r] = one.clone();
+        expected[0] = (&expected[0] + &a) % n;
+        if result != expected {
+            return false;
+        }
+    }
+    true
+}
+
+/// Element u + v x of (Z/nZ)[x] / (x^2 - b x - c)
+type Quadratic = (BigUint, BigUint);
+
+/// Multiplies in (Z/nZ)[x] / (x^2 - b x - c), using x^2 = b x + c
+fn quadratic_mul(p: &Quadratic, q: &Quadratic, b: &BigUint, c: &BigUint, n: &BigUint) -> Quadratic {
+    let high = &p.1 * &q.1 % n;
+    (
+        (&p.0 * &q.0 + c * &high) % n,
+        (&p.0 * &q.1 + &p.1 * &q.0 + b * &high) % n,
+    )
+}
+
+/// Computes x^exp in (Z/nZ)[x] / (x^2 - b x - c)
+fn quadratic_pow_x(exp: &BigUint, b: &BigUint, c: &BigUint, n: &BigUint) -> Quadratic {
+    let x = (BigUint::from(0u32), BigUint::from(1u32));
+    let mut result = (BigUint::from(1u32), BigUint::from(0u32));
+    for bit in (0..exp.bits()).rev() {
+        result = quadratic_mul(&result, &result, b, c, n);
+        if exp.bit(bit) {
+            result = quadratic_mul(&result, &x, b, c, n);
+        }
+    }
+    result
+}
+
+/// Settles the small and square inputs before the Frobenius tests. Returns
+/// `Some` when the answer is already known.
+fn frobenius_precheck(maybe_prime: &BigUint) -> Option<bool> {
+    if *maybe_prime < BigUint::from(2u32) {
+        return Some(false);
+    }
+    if maybe_prime.is_even() {
+        return Some(*maybe_prime == BigUint::from(2u32));
+    }
+    for prime in small_odd_primes(SMALL_PRIMES_BOUND) {
+        if maybe_prime % prime == BigUint::from(0u32) {
+            return Some(*maybe_prime == BigUint::from(prime));
+        }
+    }
+    // No Factor Below the Bound
+    if *maybe_prime < BigUint::from(SMALL_PRIMES_BOUND).pow(2u32) {
+        return Some(true);
+    }
+    let root = maybe_prime.sqrt();
+    if &root * &root == *maybe_prime {
+        return Some(false);
+    }
+    None
+}
+
+/// Draws (b, c) with ((b^2 + 4c)/n) = -1 and (-c/n) = 1. Returns `None` if a
+/// factor of n shows up on the way.
+fn frobenius_parameters(maybe_prime: &BigUint, seed: &mut BigUint) -> Option<(BigUint, BigUint)> {
+    let modulus = BigUint::from(2u32).pow(maybe_prime.bits().next_power_of_two());
+    let draw = |seed: &mut BigUint| {
+        // Using MUSL parameters
+        *seed = gen_linear_congruential_generator(
+            modulus.clone(),
+            BigUint::from_i64(6364136223846793005i64).unwrap(),
+            BigUint::from(1u32),
+            seed.clone(),
+        )
+        .unwrap();
+        seed.mod_floor(&(maybe_prime - 1u32)) + 1u32
+    };
+    loop {
+        let b = draw(seed);
+        let c = draw(seed);
+        let discriminant = (&b * &b + &c * 4u32) % maybe_prime;
+        let minus_c = maybe_prime - &c;
+        for value in [&discriminant, &minus_c, &c] {
+            let divisor = value.gcd(maybe_prime);
+            if divisor != BigUint::from(1u32) && divisor != *maybe_prime {
+                return None;
+            }
+        }
+        if jacobi_symbol(&discriminant, maybe_prime) == -1 && jacobi_symbol(&minus_c, maybe_prime) == 1 {
+            return Some((b, c));
+        }
+    }
+}
+
+/// # Quadratic Frobenius Test
+/// Grantham's Frobenius probable prime test with respect to
+/// f(x) = x^2 - bx - c, where ((b^2 + 4c)/n) = -1: in (Z/nZ)[x]/(f) the Frobenius map must
+/// send x to its conjugate, x^n ≡ b - x (which also gives x^(n+1) ≡ -c).
+/// Each round draws new (b, c).
+///
+/// ## Arguments
+/// * `maybe_prime` - The number to test
+/// * `rounds` - The number of random polynomials
+pub fn is_prime_frobenius(maybe_prime: BigUint, rounds: usize) -> bool {
+    if let Some(known) = frobenius_precheck(&maybe_prime) {
+        return known;
+    }
+    let mut seed = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
+    for _ in 0..rounds {
+        let (b, c) = match frobenius_parameters(&maybe_prime, &mut seed) {
+            Some(parameters) => parameters,
+            None => return false,
+        };
+        let conjugate = (b.clone(), &maybe_prime - 1u32);
+        if quadratic_pow_x(&maybe_prime, &b, &c, &maybe_prime) != conjugate {
+            return false;
+        }
+    }
+    true
+}
+
+/// # Strong Frobenius Test
+/// Grantham's random quadratic Frobenius test (RQFT). With f as in the
+/// quadratic Frobenius test and (-c/n) = 1, it checks that x^((n+1)/2) lies
+/// in Z/nZ, that x^(n+1) ≡ -c, and that writing n^2 - 1 = 2^r s (s odd),
+/// x^s ≡ 1 or x^(2^j s) ≡ -1 for some j ≤ r - 2. A composite passes a round
+/// with probability below 1/7710.
+///
+/// ## Arguments
+/// * `maybe_prime` - The number to test
+/// * `rounds` - The number of random polynomials
+pub fn is_prime_strong_frobenius(maybe_prime: BigUint, rounds: usize) -> bool {
+    if let Some(known) = frobenius_precheck(&maybe_prime) {
+        return known;
+    }
+    let n = &maybe_prime;
+    let zero = BigUint::from(0u32);
+    let one = (BigUint::from(1u32), zero.clone());
+    let minus_one = (n - 1u32, zero.clone());
+    // Write n^2 - 1 = 2^r s
+    let square_minus_one = n * n - 1u32;
+    let r = square_minus_one.trailing_zeros().unwrap();
+    let s = &square_minus_one >> r;
+    let mut seed = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
+    for _ in 0..rounds {
+        let (b, c) = match frobenius_parameters(n, &mut seed) {
+            Some(parameters) => parameters,
+            None => return false,
+        };
+        // x^((n+1)/2) in Z/nZ and x^(n+1) ≡ -c
+        let half = quadratic_pow_x(&((n + 1u32) >> 1), &b, &c, n);
+        if half.1 != zero {
+            return false;
+        }
+        if quadratic_mul(&half, &half, &b, &c, n) != (n - &c, zero.clone()) {
+            return false;
+        }
+        // Square Roots of 1
+        let mut power = quadratic_pow_x(&s, &b, &c, n);
+        if power == one || power == minus_one {
+            continue;
+        }
+        let mut found = false;
+        for _ in 0..r.saturating_sub(2) {
+            power = quadratic_mul(&power, &power, &b, &c, n);
+            if power == minus_one {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return false;
+        }
+    }
+    true
+}
+
+/// # Solovay-Strassen Test
+/// Euler probable prime test: for a random base a, n passes if
+/// a^((n-1)/2) ≡ (a/n) (mod n) with (a/n) the Jacobi symbol. A composite
+/// passes a round with probability at most 1/2.
+///
+/// ## Arguments
+/// * `maybe_prime` - The number to test
+/// * `rounds` - The number of random bases
+pub fn is_prime_solovay_strassen(maybe_prime: BigUint, rounds: usize) -> bool {
+    let big_two = BigUint::from(2u32);
+    if maybe_prime == big_two || maybe_prime == BigUint::from(3u32) {
+        return true;
+    }
+    if maybe_prime < big_two || maybe_prime.is_even() {
+        return false;
+    }
+    let minus_one = &maybe_prime - 1u32;
+    let half = &minus_one >> 1;
+    let mut seed = BigUint::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis());
+    for _ in 0..rounds {
+        // Generate a Random Number (Using MUSL parameters)
+        seed = gen_linear_congruential_generator(
+            BigUint::from(2u32).pow(maybe_prime.bits().next_power_of_two()),
+            BigUint::from_i64(6364136223846793005i64).unwrap(),
+            BigUint::from(1u32),
+            seed,
+        )
+        .unwrap();
+        // Take the Base a in [2, n - 2]
+        let base = seed.mod_floor(&(&maybe_prime - 3u32)) + &big_two;
+        let expected = match jacobi_symbol(&base, &maybe_prime) {
+            0 => return false,
+            1 => BigUint::from(1u32),
+            _ => minus_one.clone(),
+        };
+        if base.modpow(&half, &maybe_prime) != expected {
+            return false;
+        }
+    }
+    true
+}
+
+/// Strong probable prime test of an odd n > 2 to a single base
+fn is_strong_probable_prime(n: &BigUint, base: &BigUint) -> bool {
+    let minus_one = n - 1u32;
+    let s = minus_one.trailing_zeros().unwrap();
+    let mut x = base.modpow(&(&minus_one >> s), n);
+    if x == BigUint::from(1u32) || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = &x * &x % n;
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// Halves x modulo the odd n
+fn half_mod(x: BigUint, n: &BigUint) -> BigUint {
+    if x.is_odd() {
+        (x + n) >> 1
+    } else {
+        x >> 1
+    }
+}
+
+/// Strong Lucas probable prime test of an odd non-square n with Selfridge's
+/// parameters: the first D in 5, -7, 9, -11, ... with (D/n) = -1, P = 1 and
+/// Q = (1 - D)/4.
+pub(crate) fn is_strong_lucas_probable_prime(n: &BigUint) -> bool {
+    // Find D (Terminates as n is not a Square)
+    let mut magnitude = 5u32;
+    let mut negative = false;
+    let d = loop {
+        let absolute = BigUint::from(magnitude) % n;
+        let d = if negative { (n - &absolute) % n } else { absolute };
+        match jacobi_symbol(&d, n) {
+            -1 => break d,
+            0 => return false,
+            _ => {}
+        }
+        magnitude += 2;
+        negative = !negative;
+    };
+    // Q = (1 - D) / 4 mod n
+    let q = half_mod(half_mod((n + 1u32 - &d) % n, n), n);
+    // Write n + 1 = 2^s k
+    let plus_one = n + 1u32;
+    let s = plus_one.trailing_zeros().unwrap();
+    let k = &plus_one >> s;
+    // Compute U_k, V_k and Q^k from the Top Bit Down (P = 1)
+    let (mut u, mut v, mut q_power) = (BigUint::from(1u32), BigUint::from(1u32), q.clone());
+    for bit in (0..k.bits() - 1).rev() {
+        u = &u * &v % n;
+        v = (&v * &v + n * 2u32 - &q_power * 2u32 % n) % n;
+        q_power = &q_power * &q_power % n;
+        if k.bit(bit) {
+            let next_u = half_mod(&u + &v, n);
+            v = half_mod(&d * &u + &v, n) % n;
+            u = next_u % n;
+            q_power = &q_power * &q % n;
+        }
+    }
+    let zero = BigUint::from(0u32);
+    if u == zero || v == zero {
+        return true;
+    }
+    // V_(2^r k) for r < s
+    for _ in 1..s {
+        v = (&v * &v + n * 2u32 - &q_power * 2u32 % n) % n;
+        if v == zero {
+            return true;
+        }
+        q_power = &q_power * &q_power % n;
+    }
+    false
+}
+
+/// # Baillie-PSW Test
+/// A strong probable prime test to base 2 followed by a strong Lucas
+/// probable prime test. No composite passing both is known, and none exists
+/// below 2^64. The test is deterministic.
+///
+/// ## Arguments
+/// * `maybe_prime` - The number to test
+pub fn is_prime_bpsw(maybe_prime: &BigUint) -> bool {
+    if let Some(known) = frobenius_precheck(maybe_prime) {
+        return known;
+    }
+    is_strong_probable_prime(maybe_prime, &BigUint::from(2u32)) && is_strong_lucas_probable_prime(maybe_prime)
+}
+
+/// # Primality Test
+/// Common interface of the primality tests, so generators and other callers
+/// can take any of them (or a custom one) as a parameter. `rounds` is the
+/// number of random bases for probabilistic tests and is ignored by the
+/// deterministic ones. Tests are shared between threads by the generators.
+pub trait PrimalityTest: Send + Sync {
+    /// Short name used in reports
+    fn name(&self) -> &'static str;
+
+    /// Returns `false` if `maybe_prime` is composite, `true` if it is (probably) prime
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool;
+}
+
+/// Fermat test (`is_prime_fermat`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fermat;
+
+/// Fermat test spread over threads (`is_prime_fermat_parallel`)
+#[derive(Debug, Clone, Copy)]
+pub struct FermatParallel {
+    pub threads: usize,
+}
+
+/// Miller-Rabin test (`is_prime_miller_rabin`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MillerRabin;
+
+/// Solovay-Strassen test (`is_prime_solovay_strassen`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SolovayStrassen;
+
+/// Baillie-PSW test (`is_prime_bpsw`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bpsw;
+
+/// Quadratic Frobenius test (`is_prime_frobenius`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Frobenius;
+
+/// Strong Frobenius test (`is_prime_strong_frobenius`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrongFrobenius;
+
+/// AKS test (`is_prime_aks`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Aks;
+
+impl PrimalityTest for Fermat {
+    fn name(&self) -> &'static str {
+        "Fermat"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_fermat(maybe_prime.clone(), rounds)
+    }
+}
+
+impl PrimalityTest for FermatParallel {
+    fn name(&self) -> &'static str {
+        "Fermat (Parallel)"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_fermat_parallel(maybe_prime.clone(), rounds, self.threads)
+    }
+}
+
+impl PrimalityTest for MillerRabin {
+    fn name(&self) -> &'static str {
+        "Miller-Rabin"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_miller_rabin(maybe_prime.clone(), rounds)
+    }
+}
+
+impl PrimalityTest for SolovayStrassen {
+    fn name(&self) -> &'static str {
+        "Solovay-Strassen"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_solovay_strassen(maybe_prime.clone(), rounds)
+    }
+}
+
+impl PrimalityTest for Bpsw {
+    fn name(&self) -> &'static str {
+        "BPSW"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, _rounds: usize) -> bool {
+        is_prime_bpsw(maybe_prime)
+    }
+}
+
+impl PrimalityTest for Frobenius {
+    fn name(&self) -> &'static str {
+        "Frobenius"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_frobenius(maybe_prime.clone(), rounds)
+    }
+}
+
+impl PrimalityTest for StrongFrobenius {
+    fn name(&self) -> &'static str {
+        "Strong Frobenius"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, rounds: usize) -> bool {
+        is_prime_strong_frobenius(maybe_prime.clone(), rounds)
+    }
+}
+
+impl PrimalityTest for Aks {
+    fn name(&self) -> &'static str {
+        "AKS"
+    }
+
+    fn is_prime(&self, maybe_prime: &BigUint, _rounds: usize) -> bool {
+        is_prime_aks(maybe_prime)
+    }
+}
+
+/// # Residue Class
+/// Restricts generated primes to p ≡ `residue` (mod `modulus`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Congruence {
+    pub residue: BigUint,
+    pub modulus: BigUint,
+}
+
+impl Congruence {
+    /// The class `residue` mod `modulus`. Fails unless it holds infinitely
+    /// many primes: `modulus` must be positive and gcd(residue, modulus) = 1
+    pub fn new(residue: BigUint, modulus: BigUint) -> Result<Congruence, GenerationError> {
+        if modulus == BigUint::from(0u32) {
+            return Err(GenerationError::InvalidCongruence(residue, modulus));
+        }
+        let residue = residue % &modulus;
+        if residue.gcd(&modulus) != BigUint::from(1u32) {
+            return Err(GenerationError::InvalidCongruence(residue, modulus));
+        }
+        Ok(Congruence { residue, modulus })
+    }
+
+    /// Blum primes, p ≡ 3 (mod 4)
+    pub fn blum() -> Congruence {
+        Congruence { residue: BigUint::from(3u32), modulus: BigUint::from(4u32) }
+    }
+
+    /// NTT friendly primes, p ≡ 1 (mod 2^k)
+    pub fn ntt(k: u32) -> Congruence {
+        Congruence { residue: BigUint::from(1u32), modulus: BigUint::from(1u32) << k }
+    }
+
+    /// Whether `value` is in the class
+    pub fn contains(&self, value: &BigUint) -> bool {
+        value % &self.modulus == self.residue
+    }
+
+    /// Moves `value` to a nearby odd member of the class
+    pub fn apply(&self, value: &BigUint) -> BigUint {
+        let mut moved = value - value % &self.modulus + &self.residue;
+        if moved.is_even() {
+            moved += &self.modulus;
+        }
+        moved
+    }
+
+    /// Fails if no odd member of the class in [`low`, `high`) can be prime:
+    /// the class has no odd member there, or has at most
+    /// `RANGE_ENUMERATION_LIMIT` of them and none passes BPSW
+    pub(crate) fn check_range(&self, low: &BigUint, high: &BigUint) -> Result<(), GenerationError> {
+        let empty = || GenerationError::NoPrimeInRange(low.clone(), high - 1u32);
+        // Smallest Odd Member at or Above low
+        let mut first = low + (&self.residue + &self.modulus - low % &self.modulus) % &self.modulus;
+        if first.is_even() {
+            if self.modulus.is_even() {
+                return Err(empty());
+            }
+            first += &self.modulus;
+        }
+        if first >= *high {
+            return Err(empty());
+        }
+        // Odd Members Are One (Even Modulus) or Two (Odd Modulus) Moduli Apart
+        let step = if self.modulus.is_even() { self.modulus.clone() } else { &self.modulus << 1 };
+        let count = (high - 1u32 - &first) / &step + 1u32;
+        if count <= BigUint::from(RANGE_ENUMERATION_LIMIT) {
+            let mut member = first;
+            while member < *high {
+                if is_prime_bpsw(&member) {
+                    return Ok(());
+                }
+                member += &step;
             }
-            current_random = maybe_prime;
+            return Err(empty());
         }
+        Ok(())
     }
 }
 
-pub fn gen_prime_number_lcg_fermat_parallel(bit_length: usize, strongness: usize, seed: BigUint, threads: usize) -> BigUint {
+/// Predicate p ≢ 1 (mod e) for a prime public exponent e, so that e is
+/// invertible modulo p - 1 as RSA requires
+pub fn rsa_exponent_compatible(e: u64) -> impl Fn(&BigUint) -> bool {
+    move |value: &BigUint| value % e != BigUint::from(1u32)
+}
+
+/// # LCG Prime Generation
+/// Draws odd `bit_length`-bit candidates (MSB = 1) from a Linear
+/// Congruential Generator with POSIX parameters until one passes `test`.
+/// Fails on bit lengths below 2 or once one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `test` - The primality test applied to each candidate
+/// * `bit_length` - The bit length of the generated prime (MSB = 1)
+/// * `strongness` - The number of rounds passed to the test
+/// * `seed` - The seed of the Linear Congruential Generator
+/// * `limits` - When to give up
+pub fn gen_prime_number_lcg<T: PrimalityTest + ?Sized>(
+    test: &T,
+    bit_length: usize,
+    strongness: usize,
+    seed: BigUint,
+    limits: &Limits,
+) -> Result<BigUint, GenerationError> {
+    gen_prime_number_lcg_constrained(test, bit_length, strongness, seed, None, |_| true, limits)
+}
+
+/// # Constrained LCG Prime Generation
+/// Same candidate loop as `gen_prime_number_lcg`, but each candidate is
+/// first moved into the residue class `congruence` (if any) and must satisfy
+/// `predicate` before the primality test runs. Candidates pushed out of the
+/// bit length by the move are skipped (and still count as candidates). Fails
+/// up front if the class provably holds no prime of the bit length.
+///
+/// Without a class the generator runs modulo 2^(`bit_length`-1), as it always
+/// has, so the bit below the MSB stays clear. With a class it runs modulo
+/// 2^`bit_length`: the move only lowers a candidate to its class member,
+/// and classes whose primes all have that bit set must stay reachable.
+///
+/// ## Arguments
+/// * `test` - The primality test applied to each candidate
+/// * `bit_length` - The bit length of the generated prime (MSB = 1)
+/// * `strongness` - The number of rounds passed to the test
+/// * `seed` - The seed of the Linear Congruential Generator
+/// * `congruence` - The residue class of the prime
+/// * `predicate` - Any extra condition on the prime
+/// * `limits` - When to give up
+pub fn gen_prime_number_lcg_constrained<T, P>(
+    test: &T,
+    bit_length: usize,
+    strongness: usize,
+    seed: BigUint,
+    congruence: Option<&Congruence>,
+    predicate: P,
+    limits: &Limits,
+) -> Result<BigUint, GenerationError>
+where
+    T: PrimalityTest + ?Sized,
+    P: Fn(&BigUint) -> bool,
+{
+    if bit_length < 2 {
+        return Err(GenerationError::InvalidBitLength(bit_length));
+    }
+    // Refuse Classes Without a Prime of the Bit Length
+    if let Some(congruence) = congruence {
+        let low = BigUint::from(1u32) << (bit_length - 1);
+        congruence.check_range(&low, &(&low << 1))?;
+    }
+    // Moving to the Class Needs the Whole Bit Length
+    let modulus_bits = if congruence.is_some() { bit_length } else { bit_length - 1 };
     // Try Generate a Value
-    let mut current_random = seed.clone();
+    let mut current_random = seed;
+    let mut candidates: u64 = 0;
     loop {
+        limits.check(candidates)?;
+        candidates += 1;
         // Generate a Random Number (With POSIX parameters)
         let random = gen_linear_congruential_generator(
-            BigUint::from_i32(2).unwrap().pow(bit_length),
+            BigUint::from_i32(2).unwrap().pow(modulus_bits),
             BigUint::from(25214903917u64),
             BigUint::from(11u64),
             current_random.clone(),
         );
         // Continue if Error
-        if random.is_ok() {
+        if let Ok(random) = random {
+            let mut maybe_prime: BigUint = random >> 1;
+            maybe_prime.set_bit(u64::from_usize(bit_length - 1).unwrap(), true);
+            maybe_prime.set_bit(0, true);
+            current_random = maybe_prime.clone();
+            // Apply the Constraints
+            if let Some(congruence) = congruence {
+                maybe_prime = congruence.apply(&maybe_prime);
+                if maybe_prime.bits() as usize != bit_length {
+                    continue;
+                }
+            }
+            if !predicate(&maybe_prime) {
+                continue;
+            }
             // Check Prime
-            let maybe_prime = random.unwrap();
-            if is_prime_fermat_parallel(maybe_prime.clone(), strongness, threads) {
+            if test.is_prime(&maybe_prime, strongness) {
                 // Return Value
-                return maybe_prime;
+                return Ok(maybe_prime);
             }
-            current_random = maybe_prime;
         }
     }
 }
 
-
 #[test]
 fn test_is_prime_miller_rabin() {
     // Test Prime for 9973
@@ -247,7 +1160,7 @@ fn test_gen_prime_number_lcg_miller_64bits() {
             .unwrap()
             .as_secs(),
     );
-    let prime = gen_prime_number_lcg_miller(64, 10, now);
+    let prime = gen_prime_number_lcg_miller(64, 10, now, &Limits::none()).unwrap();
     println!("Generated Prime: {}", prime);
 }
 
@@ -281,3 +1194,279 @@ fn test_is_prime_fermat_4096b() {
     let is_prime = is_prime_fermat(value, 10);
     println!("[Fermat] Result: {}", is_prime)
 }
+
+#[test]
+fn test_gen_prime_number_incremental_miller_64bits() {
+    // Generate Prime
+    let now = BigUint::from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    );
+    let prime = gen_prime_number_incremental_miller(64, 10, now, &Limits::none()).unwrap();
+    println!("Generated Prime: {}", prime);
+    assert_eq!(prime.bits(), 64);
+    assert!(is_prime_fermat(prime, 10));
+}
+
+#[test]
+fn test_gen_prime_number_incremental_miller_small() {
+    // Candidates Below the Small Primes Bound Must Not Be Discarded
+    for seed in 0..20u32 {
+        let prime = gen_prime_number_incremental_miller(8, 10, BigUint::from(seed), &Limits::none()).unwrap();
+        assert_eq!(prime.bits(), 8);
+        assert!(small_odd_primes(256).contains(&prime.to_u32().unwrap()));
+    }
+}
+
+#[test]
+fn test_is_prime_u64() {
+    use crate::sieve::primes_up_to;
+    let primes = primes_up_to(100_000);
+    for value in 0..100_000u64 {
+        assert_eq!(is_prime_u64(value), primes.binary_search(&value).is_ok(), "{}", value);
+    }
+    // Strong Pseudoprimes to Several Bases and Large Primes
+    assert!(!is_prime_u64(3215031751));
+    assert!(!is_prime_u64(3825123056546413051));
+    assert!(is_prime_u64(18446744073709551557));
+    assert!(!is_prime_u64(18446744073709551555));
+}
+
+#[test]
+fn test_jacobi_symbol() {
+    // Euler's Criterion for a Prime Modulus
+    let prime = BigUint::from(1009u32);
+    for a in 0..1009u32 {
+        let euler = BigUint::from(a).modpow(&BigUint::from(504u32), &prime);
+        let expected = if a == 0 { 0 } else if euler == BigUint::from(1u32) { 1 } else { -1 };
+        assert_eq!(jacobi_symbol(&BigUint::from(a), &prime), expected);
+    }
+    // Composite Moduli
+    assert_eq!(jacobi_symbol(&BigUint::from(1001u32), &BigUint::from(9907u32)), -1);
+    assert_eq!(jacobi_symbol(&BigUint::from(19u32), &BigUint::from(45u32)), 1);
+    assert_eq!(jacobi_symbol(&BigUint::from(21u32), &BigUint::from(45u32)), 0);
+}
+
+#[test]
+fn test_is_prime_aks() {
+    use crate::sieve::primes_up_to;
+    let primes = primes_up_to(400);
+    for value in 0..400u64 {
+        let expected = primes.binary_search(&value).is_ok();
+        assert_eq!(is_prime_aks(&BigUint::from(value)), expected, "{}", value);
+    }
+    // Carmichael Numbers, a Perfect Power and Larger Primes
+    for composite in [561u64, 41041, 1_030_301, 1_046_529] {
+        assert!(!is_prime_aks(&BigUint::from(composite)), "{}", composite);
+    }
+    for prime in [1009u64, 4099] {
+        assert!(is_prime_aks(&BigUint::from(prime)), "{}", prime);
+    }
+    // Smallest Factor Above r: Only the Polynomial Congruence Rejects These
+    for composite in [1009u64 * 1013, 4099 * 4111, 65537 * 65539] {
+        assert!(!is_prime_aks(&BigUint::from(composite)), "{}", composite);
+    }
+    // A Prime Running the Whole Congruence Loop (r = 311, 299 Values of a)
+    assert!(is_prime_aks(&BigUint::from(65537u32)));
+}
+
+#[test]
+fn test_is_prime_frobenius() {
+    use crate::sieve::primes_in_range;
+    // Beyond the Trial Division Shortcut
+    let low = 1_000_000_000u64;
+    let primes = primes_in_range(low, low + 20_000);
+    for value in (low + 1..low + 20_000).step_by(2) {
+        let expected = primes.binary_search(&value).is_ok();
+        assert_eq!(is_prime_frobenius(BigUint::from(value), 1), expected, "{}", value);
+        assert_eq!(is_prime_strong_frobenius(BigUint::from(value), 1), expected, "{}", value);
+    }
+    // Strong Pseudoprime to Bases 2-7, Carmichael Number and a Mersenne Prime
+    let carmichael = BigUint::from_str("1590231231043178376951698401").unwrap();
+    let mersenne = BigUint::from_str("170141183460469231731687303715884105727").unwrap();
+    for rounds in [1, 5] {
+        assert!(!is_prime_frobenius(BigUint::from(3215031751u64), rounds));
+        assert!(!is_prime_strong_frobenius(BigUint::from(3215031751u64), rounds));
+        assert!(!is_prime_frobenius(carmichael.clone(), rounds));
+        assert!(!is_prime_strong_frobenius(carmichael.clone(), rounds));
+        assert!(is_prime_frobenius(mersenne.clone(), rounds));
+        assert!(is_prime_strong_frobenius(mersenne.clone(), rounds));
+    }
+}
+
+#[test]
+fn test_primality_test_trait() {
+    use crate::sieve::primes_in_range;
+    let tests: Vec<Box<dyn PrimalityTest>> = vec![
+        Box::new(Fermat),
+        Box::new(FermatParallel { threads: 4 }),
+        Box::new(MillerRabin),
+        Box::new(SolovayStrassen),
+        Box::new(Bpsw),
+        Box::new(Frobenius),
+        Box::new(StrongFrobenius),
+    ];
+    // Compare With the Sieve Beyond the Trial Division Shortcut
+    let low = 1_000_000_000u64;
+    let primes = primes_in_range(low, low + 4_000);
+    for value in (low + 1..low + 4_000).step_by(2) {
+        let expected = primes.binary_search(&value).is_ok();
+        for test in &tests {
+            assert_eq!(test.is_prime(&BigUint::from(value), 10), expected, "{} {}", test.name(), value);
+        }
+    }
+    // Strong Pseudoprime to Bases 2-17 and a Carmichael Number (Its Small Factors Catch Fermat)
+    let pseudoprime = BigUint::from(341550071728321u64);
+    let carmichael = BigUint::from_str("1590231231043178376951698401").unwrap();
+    for test in &tests {
+        assert!(!test.is_prime(&pseudoprime, 64), "{}", test.name());
+        assert!(!test.is_prime(&carmichael, 64), "{}", test.name());
+    }
+    assert!(!is_prime_bpsw(&BigUint::from(3215031751u64)));
+    assert!(!is_prime_solovay_strassen(BigUint::from(3215031751u64), 10));
+    // Every Test Drives the Same Generator
+    for test in &tests {
+        let prime = gen_prime_number_lcg(test.as_ref(), 128, 10, BigUint::from(12345u32), &Limits::none()).unwrap();
+        assert_eq!(prime.bits(), 128);
+        assert!(is_prime_bpsw(&prime), "{}", test.name());
+    }
+}
+
+#[test]
+fn test_gen_prime_number_lcg_constrained() {
+    let seed = BigUint::from(2024u32);
+    // Blum Prime
+    let prime = gen_prime_number_lcg_constrained(&Bpsw, 128, 0, seed.clone(), Some(&Congruence::blum()), |_| true, &Limits::none()).unwrap();
+    assert!(is_prime_bpsw(&prime) && prime.bits() == 128);
+    assert_eq!(prime % 4u32, BigUint::from(3u32));
+    // NTT Prime, p ≡ 1 (mod 2^32)
+    let ntt = Congruence::ntt(32);
+    let prime = gen_prime_number_lcg_constrained(&Bpsw, 96, 0, seed.clone(), Some(&ntt), |_| true, &Limits::none()).unwrap();
+    assert!(is_prime_bpsw(&prime) && prime.bits() == 96 && ntt.contains(&prime));
+    // RSA With e = 3 and an Extra Condition
+    let compatible = rsa_exponent_compatible(3);
+    let prime = gen_prime_number_lcg_constrained(
+        &MillerRabin,
+        64,
+        10,
+        seed,
+        None,
+        |p: &BigUint| compatible(p) && p.bit(1),
+        &Limits::none(),
+    )
+    .unwrap();
+    assert!(is_prime_bpsw(&prime));
+    assert_eq!(prime % 6u32, BigUint::from(5u32));
+}
+
+#[test]
+fn test_gen_prime_number_lcg_constrained_infeasible() {
+    let seed = BigUint::from(2024u32);
+    let low = BigUint::from(1u64 << 63);
+    let high = BigUint::from(u64::MAX);
+    let generate = |congruence: &Congruence| {
+        gen_prime_number_lcg_constrained(&Bpsw, 64, 0, seed.clone(), Some(congruence), |_| true, &Limits::none())
+    };
+    // No Member of 1 mod 2^64 Has 64 Bits
+    assert_eq!(generate(&Congruence::ntt(64)), Err(GenerationError::NoPrimeInRange(low.clone(), high.clone())));
+    // The 8 Members of 1 mod 2^60 With 64 Bits Are All Composite
+    assert_eq!(generate(&Congruence::ntt(60)), Err(GenerationError::NoPrimeInRange(low.clone(), high)));
+    // Of the 16 Members of 1 mod 2^59 Only 2^63 + 11 · 2^59 + 1 Is Prime
+    assert_eq!(generate(&Congruence::ntt(59)), Ok(low + (BigUint::from(11u32) << 59) + 1u32));
+    // Classes With at Most One Prime Are Refused
+    let class = |residue: u32, modulus: u32| Congruence::new(BigUint::from(residue), BigUint::from(modulus));
+    assert_eq!(class(1, 0), Err(GenerationError::InvalidCongruence(BigUint::from(1u32), BigUint::from(0u32))));
+    assert_eq!(class(10, 4), Err(GenerationError::InvalidCongruence(BigUint::from(2u32), BigUint::from(4u32))));
+    assert_eq!(class(7, 4), Ok(Congruence::blum()));
+}
+
+#[test]
+fn test_gen_prime_number_lcg_output() {
+    // Pinned Against an Independent Python Model of the Generator (sympy.isprime)
+    let seed = BigUint::from(12345u32);
+    let prime = gen_prime_number_lcg_miller(64, 20, seed.clone(), &Limits::none()).unwrap();
+    assert_eq!(prime, BigUint::from(9467658843253917167u64));
+    let prime = gen_prime_number_lcg(&Bpsw, 128, 0, seed, &Limits::none()).unwrap();
+    assert_eq!(prime, BigUint::from_str("170945796763925203482630472926570085937").unwrap());
+    // The Bit Below the MSB Stays Clear, as in the Original Generator
+    assert!((0..16u32).all(|seed| !gen_prime_number_lcg(&Bpsw, 64, 0, BigUint::from(seed), &Limits::none()).unwrap().bit(62)));
+}
+
+#[test]
+fn test_generation_limits() {
+    use crate::generator::CancellationToken;
+    use std::time::Duration;
+    let seed = BigUint::from(77u32);
+    // Bad Bit Lengths Fail Instead of Spinning
+    assert_eq!(gen_prime_number_lcg_miller(1, 5, seed.clone(), &Limits::none()), Err(GenerationError::InvalidBitLength(1)));
+    assert_eq!(gen_prime_number_incremental_miller(0, 5, seed.clone(), &Limits::none()), Err(GenerationError::InvalidBitLength(0)));
+    // The Legacy Wrappers Honour the Limits Too
+    let limits = Limits::none().max_candidates(0);
+    assert_eq!(gen_prime_number_lcg_miller(64, 5, seed.clone(), &limits), Err(GenerationError::CandidateLimitReached(0)));
+    assert_eq!(gen_prime_number_lcg_fermat(64, 5, seed.clone(), &limits), Err(GenerationError::CandidateLimitReached(0)));
+    assert_eq!(gen_prime_number_lcg_fermat_parallel(64, 5, seed.clone(), 2, &limits), Err(GenerationError::CandidateLimitReached(0)));
+    // The Incremental Walk Is Checked per Candidate, Not per Start
+    let limits = Limits::none().max_candidates(3);
+    assert_eq!(gen_prime_number_incremental_miller(4096, 5, seed.clone(), &limits), Err(GenerationError::CandidateLimitReached(3)));
+    // An Impossible Predicate Stops at a Limit
+    let never = |_: &BigUint| false;
+    let limits = Limits::none().max_candidates(1000);
+    assert_eq!(
+        gen_prime_number_lcg_constrained(&MillerRabin, 64, 5, seed.clone(), None, never, &limits),
+        Err(GenerationError::CandidateLimitReached(1000))
+    );
+    let limits = Limits::none().timeout(Duration::from_millis(20));
+    assert_eq!(
+        gen_prime_number_lcg_constrained(&MillerRabin, 64, 5, seed.clone(), None, never, &limits),
+        Err(GenerationError::DeadlineExceeded)
+    );
+    let token = CancellationToken::new();
+    let limits = Limits::none().token(token.clone());
+    let handle = std::thread::spawn(move || {
+        gen_prime_number_lcg_constrained(&MillerRabin, 64, 5, seed, None, never, &limits)
+    });
+    std::thread::sleep(Duration::from_millis(20));
+    token.cancel();
+    assert_eq!(handle.join().unwrap(), Err(GenerationError::Cancelled));
+}
+
+#[test]
+fn test_is_prime_miller_rabin_seeded() {
+    // 3215031751 = 151 · 751 · 28351 Fools the Bases 2, 3, 5 and 7
+    let n = BigUint::from(3215031751u64);
+    let verdicts = || -> Vec<bool> {
+        (0..64u32).map(|seed| is_prime_miller_rabin_seeded(n.clone(), 1, BigUint::from(seed))).collect()
+    };
+    // The Same Seed Gives the Same Verdict
+    assert_eq!(verdicts(), verdicts());
+    assert!(!is_prime_miller_rabin_seeded(n.clone(), 20, BigUint::from(1u32)));
+    assert!(is_prime_miller_rabin_seeded(BigUint::from(1_000_000_007u32), 20, BigUint::from(1u32)));
+}
+
+#[test]
+fn test_miller_rabin_base_changes_every_round() {
+    // The Seed Advances, so Successive Rounds Use Different Bases
+    let n = BigUint::from(1_000_003u32);
+    let mut seed = BigUint::from(42u32);
+    let bases: Vec<BigUint> = (0..8).map(|_| miller_rabin_base(&n, &mut seed)).collect();
+    assert!(bases.windows(2).all(|pair| pair[0] != pair[1]));
+    // 3215031751 Fools Bases 2, 3, 5 and 7 but Not 20 Random Ones
+    assert!(!is_prime_miller_rabin(BigUint::from(3215031751u64), 20));
+}
+
+#[test]
+fn test_miller_rabin_base_range() {
+    // Bases Cover [2, n - 2] (a Clamp Would Almost Always Give n - 2 ≡ -1)
+    for n in [5u32, 7, 9, 1_000_003] {
+        let n = BigUint::from(n);
+        let mut seed = BigUint::from(7u32);
+        let bases: Vec<BigUint> = (0..64).map(|_| miller_rabin_base(&n, &mut seed)).collect();
+        assert!(bases.iter().all(|base| *base >= BigUint::from(2u32) && *base <= &n - 2u32));
+        assert!(bases.iter().any(|base| *base != &n - 2u32));
+    }
+    // 0 and 1 are Not Prime
+    assert!(!is_prime_miller_rabin(BigUint::from(0u32), 5));
+    assert!(!is_prime_miller_rabin(BigUint::from(1u32), 5));
+}
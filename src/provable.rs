@@ -0,0 +1,222 @@
+use num::{BigUint, Integer, ToPrimitive};
+use sha2::{Digest, Sha256};
+
+use crate::{generator::GenerationError, prime::is_prime_u64};
+
+/// A prime built by the Shawe-Taylor construction, with the values needed to
+/// reproduce (and so validate) it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvablePrime {
+    /// The prime
+    pub prime: BigUint,
+    /// The seed after the construction (FIPS 186-4 prime_seed)
+    pub prime_seed: Vec<u8>,
+    /// The candidates tried (FIPS 186-4 prime_gen_counter)
+    pub prime_gen_counter: usize,
+}
+
+/// The seed as an integer modulo 2^seedlen
+pub(crate) struct Seed {
+    value: BigUint,
+    length: usize,
+}
+
+impl Seed {
+    pub(crate) fn new(bytes: &[u8]) -> Seed {
+        Seed {
+            value: BigUint::from_bytes_be(bytes),
+            length: bytes.len(),
+        }
+    }
+
+    /// seed + offset as a seedlen bit string
+    pub(crate) fn bytes(&self, offset: usize) -> Vec<u8> {
+        let value = (&self.value + offset) % (BigUint::from(1u32) << (8 * self.length));
+        let raw = value.to_bytes_be();
+        let mut bytes = vec![0u8; self.length.saturating_sub(raw.len())];
+        bytes.extend_from_slice(&raw);
+        bytes
+    }
+
+    /// Hash(seed + offset) as an integer
+    pub(crate) fn hash<D: Digest>(&self, offset: usize) -> BigUint {
+        BigUint::from_bytes_be(&D::digest(self.bytes(offset)))
+    }
+
+    fn advance(&mut self, steps: usize) {
+        self.value = BigUint::from_bytes_be(&self.bytes(steps));
+    }
+}
+
+/// # Shawe-Taylor Random Prime
+/// ST_Random_Prime of FIPS 186-4 Appendix C.6 with the hash `D`. Primes
+/// below 33 bits are found by hashing the seed and testing exactly. Longer
+/// ones extend a recursively built prime c0 of about half the length to
+/// c = 2tc0 + 1, which is proven prime by Pocklington's criterion with a
+/// hash derived base a: gcd(a^(2t) - 1, c) = 1 and a^(c-1) ≡ 1 (mod c).
+/// The output depends only on the seed, so it can be validated by rerunning.
+///
+/// ## Arguments
+/// * `length` - The bit length of the prime (at least 2)
+/// * `input_seed` - The seed, of at least the desired security strength
+pub fn st_random_prime<D: Digest>(
+    length: usize,
+    input_seed: &[u8],
+) -> Result<ProvablePrime, GenerationError> {
+    if length < 2 {
+        return Err(GenerationError::InvalidBitLength(length));
+    }
+    let one = BigUint::from(1u32);
+    let top = &one << (length - 1);
+    if length < 33 {
+        // Steps 3 - 13: Hash Small Candidates
+        let mut seed = Seed::new(input_seed);
+        let mut prime_gen_counter = 0;
+        loop {
+            let c = seed.hash::<D>(0) ^ seed.hash::<D>(1);
+            let c = &top + c % &top;
+            let c: BigUint = (c >> 1) * 2u32 + 1u32;
+            prime_gen_counter += 1;
+            seed.advance(2);
+            if is_prime_u64(c.to_u64().unwrap()) {
+                return Ok(ProvablePrime {
+                    prime: c,
+                    prime_seed: seed.bytes(0),
+                    prime_gen_counter,
+                });
+            }
+            if prime_gen_counter > 4 * length {
+                return Err(GenerationError::SeedExhausted);
+            }
+        }
+    }
+    // Step 14: Recursive Prime of Half the Length
+    let ProvablePrime {
+        prime: c0,
+        prime_seed,
+        mut prime_gen_counter,
+    } = st_random_prime::<D>(length.div_ceil(2) + 1, input_seed)?;
+    let mut seed = Seed::new(&prime_seed);
+    // Steps 16 - 21: Random x of the Requested Length
+    let outlen = <D as Digest>::output_size() * 8;
+    let iterations = length.div_ceil(outlen) - 1;
+    let old_counter = prime_gen_counter;
+    let hash_sum = |seed: &Seed| {
+        (0..=iterations).fold(BigUint::from(0u32), |sum, i| {
+            sum + (seed.hash::<D>(i) << (i * outlen))
+        })
+    };
+    let x = hash_sum(&seed);
+    seed.advance(iterations + 1);
+    let x = &top + x % &top;
+    // Step 22: t = ⌈x / 2c0⌉
+    let two_c0: BigUint = &c0 * 2u32;
+    let mut t = x.div_ceil(&two_c0);
+    loop {
+        // Steps 23 - 24: Keep c Within the Length
+        if &two_c0 * &t + 1u32 > &one << length {
+            t = top.div_ceil(&two_c0);
+        }
+        let c: BigUint = &two_c0 * &t + 1u32;
+        prime_gen_counter += 1;
+        // Steps 26 - 30: Pocklington With a Hash Derived Base
+        let a = hash_sum(&seed);
+        seed.advance(iterations + 1);
+        let a = (a % (&c - 3u32)) + 2u32;
+        let z = a.modpow(&(&t * 2u32), &c);
+        if (&z - 1u32).gcd(&c) == one && z.modpow(&c0, &c) == one {
+            return Ok(ProvablePrime {
+                prime: c,
+                prime_seed: seed.bytes(0),
+                prime_gen_counter,
+            });
+        }
+        if prime_gen_counter >= 4 * length + old_counter {
+            return Err(GenerationError::SeedExhausted);
+        }
+        t += 1u32;
+    }
+}
+
+/// # Provable Prime Generation
+/// Shawe-Taylor construction with SHA-256 (see `st_random_prime`).
+///
+/// ## Arguments
+/// * `length` - The bit length of the prime (at least 2)
+/// * `input_seed` - The seed
+pub fn gen_provable_prime(
+    length: usize,
+    input_seed: &[u8],
+) -> Result<ProvablePrime, GenerationError> {
+    st_random_prime::<Sha256>(length, input_seed)
+}
+
+#[test]
+fn test_gen_provable_prime() {
+    use crate::prime::is_prime_bpsw;
+    let seed = [0x5au8; 32];
+    for length in [2, 3, 16, 32, 33, 64, 100, 256, 521] {
+        let provable = gen_provable_prime(length, &seed).unwrap();
+        assert_eq!(provable.prime.bits(), length as u64);
+        assert!(is_prime_bpsw(&provable.prime), "{}", length);
+        assert_eq!(provable.prime_seed.len(), seed.len());
+        // Reproducible From the Seed
+        assert_eq!(gen_provable_prime(length, &seed), Ok(provable));
+    }
+    assert_eq!(
+        gen_provable_prime(1, &seed),
+        Err(GenerationError::InvalidBitLength(1))
+    );
+}
+
+#[test]
+fn test_st_random_prime_vectors() {
+    use sha2::Sha384;
+    // Computed by a Separate Python Implementation of FIPS 186-4 C.6 (hashlib
+    // and sympy.isprime): (Length, Seed, Prime, prime_gen_counter, Seed Advance)
+    let repeated = [0x5au8; 32];
+    let counting: Vec<u8> = (0..48).collect();
+    let vectors: [(usize, &[u8], &str, usize, u32); 5] = [
+        (16, &repeated, "34841", 2, 4),
+        (100, &repeated, "927167917541636639179946439323", 19, 22),
+        (32, &counting[..32], "2775438527", 5, 10),
+        (
+            256,
+            &counting[..32],
+            "109928254445060086659700503570413794044944440159273870781891695052597556044927",
+            294,
+            303,
+        ),
+        (
+            521,
+            &counting[..32],
+            "5001430092320474848296415636832180799678846047422159153013923562677713708879885\
+             573961450632564175792467476178525622517071944554895963338017073362836383816789",
+            206,
+            393,
+        ),
+    ];
+    let check =
+        |provable: ProvablePrime, seed: &[u8], prime: &str, counter: usize, advance: u32| {
+            assert_eq!(provable.prime.to_string(), prime);
+            assert_eq!(provable.prime_gen_counter, counter);
+            assert_eq!(
+                BigUint::from_bytes_be(&provable.prime_seed),
+                BigUint::from_bytes_be(seed) + advance
+            );
+        };
+    for (length, seed, prime, counter, advance) in vectors {
+        check(
+            st_random_prime::<Sha256>(length, seed).unwrap(),
+            seed,
+            prime,
+            counter,
+            advance,
+        );
+    }
+    // A Longer Digest Changes the Iterations of the Recursive Steps
+    let prime = "6789382623422307133243987582692138874784354408349456846971481102295271402881998\
+                 521691705903129032757336825049204360699063551400985344315587889508635891584307";
+    let provable = st_random_prime::<Sha384>(521, &counting).unwrap();
+    check(provable, &counting, prime, 443, 687);
+}
@@ -88,6 +88,100 @@ pub fn gen_linear_congruential_generator(
     Ok(current_iteration)
 }
 
+/// # Random Generator
+/// Source of random bits for the prime generators.
+pub trait RandomGenerator: Send {
+    /// Returns a uniformly distributed number below 2^`bits`
+    fn gen_bits(&mut self, bits: usize) -> BigUint;
+
+    /// Returns a number in [0, `bound`), by rejection sampling
+    fn gen_below(&mut self, bound: &BigUint) -> BigUint {
+        let bits = bound.bits() as usize;
+        loop {
+            let value = self.gen_bits(bits);
+            if value < *bound {
+                return value;
+            }
+        }
+    }
+}
+
+/// Linear Congruential Generator with POSIX parameters (a = 25214903917,
+/// C = 11, N = 2^48), keeping the upper 32 bits of each state like drand48
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: BigUint,
+}
+
+impl Lcg {
+    /// Starts the sequence at `seed`
+    pub fn new(seed: BigUint) -> Lcg {
+        Lcg { state: seed }
+    }
+
+    /// Seeds the sequence with the current time in milliseconds
+    pub fn from_time() -> Lcg {
+        Lcg::new(BigUint::from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis(),
+        ))
+    }
+}
+
+impl RandomGenerator for Lcg {
+    fn gen_bits(&mut self, bits: usize) -> BigUint {
+        let mut value = BigUint::from(0u32);
+        for _ in 0..bits.div_ceil(32) {
+            self.state = gen_linear_congruential_generator(
+                BigUint::from(1u64 << 48),
+                BigUint::from(25214903917u64),
+                BigUint::from(11u64),
+                self.state.clone(),
+            )
+            .unwrap();
+            value = (value << 32u32) | (&self.state >> 16u32);
+        }
+        value >> (bits.div_ceil(32) * 32 - bits)
+    }
+}
+
+/// Blum Blum Shub generator, one bit (the parity) per squaring
+#[derive(Debug, Clone)]
+pub struct BlumBlumShub {
+    modulus: BigUint,
+    state: BigUint,
+}
+
+impl BlumBlumShub {
+    /// Uses N = p * q with the primes p and q, starting at `seed`
+    pub fn new(p_val: BigUint, q_val: BigUint, seed: BigUint) -> Result<BlumBlumShub, Error> {
+        // Check Seed - MDC (seed, p*q) = 1
+        for prime in [&p_val, &q_val] {
+            if seed.mod_floor(prime) == BigUint::from(0u32) {
+                return Err(Error::NotCoPrimeError(seed, prime.clone()));
+            }
+        }
+        Ok(BlumBlumShub {
+            modulus: p_val * q_val,
+            state: seed,
+        })
+    }
+}
+
+impl RandomGenerator for BlumBlumShub {
+    fn gen_bits(&mut self, bits: usize) -> BigUint {
+        let mut generated_number: BitVec<Lsb0, u8> = bitvec![Lsb0, u8; 0; bits];
+        for idx in 0..bits {
+            // Xn = (Xn-1)^2 mod N
+            self.state = &self.state * &self.state % &self.modulus;
+            generated_number.set(idx, self.state.bit(0));
+        }
+        BigUint::from_bytes_le(generated_number.as_raw_slice())
+    }
+}
+
 #[test]
 fn bbs_test_100b() {
     let value = gen_blum_blum_shub(
@@ -141,4 +235,22 @@ fn lcg_test_100b_x100() {
         .unwrap();
         println!("{}", value)
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_random_generators() {
+    let mut lcg = Lcg::new(BigUint::from(42u32));
+    let mut bbs = BlumBlumShub::new(
+        BigUint::from(30000000091u64),
+        BigUint::from(40000000003u64),
+        BigUint::from(4882516701u64),
+    )
+    .unwrap();
+    let bound = BigUint::from(1000u32);
+    for bits in [1, 31, 32, 33, 100] {
+        assert!(lcg.gen_bits(bits).bits() <= bits as u64);
+        assert!(bbs.gen_bits(bits).bits() <= bits as u64);
+        assert!(lcg.gen_below(&bound) < bound);
+    }
+    // Reaches the Top Bit
+    assert!((0..64).any(|_| lcg.gen_bits(100).bits() == 100));
+}
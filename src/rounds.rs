@@ -0,0 +1,120 @@
+use std::f64::consts::{LN_2, PI};
+
+/// Number of Miller-Rabin rounds and the error bound they achieve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundEstimate {
+    /// Rounds of Miller-Rabin with random bases
+    pub rounds: usize,
+    /// log2 of the achieved error probability
+    pub log2_error: f64,
+}
+
+/// log2(2^a + 2^b + ...) without leaving the log domain
+fn log2_sum(terms: &[f64]) -> f64 {
+    let max = terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    max + terms
+        .iter()
+        .map(|term| (term - max).exp2())
+        .sum::<f64>()
+        .log2()
+}
+
+/// # Average Case Error Bound
+/// log2 of the Damgård-Landrock-Pomerance bound on the probability that a
+/// random odd `bit_length`-bit integer passing `rounds` Miller-Rabin rounds
+/// is composite, as evaluated in FIPS 186-4 Appendix F.1 (the source of the
+/// FIPS 186-5 round tables). Only valid for candidates drawn at random; for
+/// inputs chosen by an adversary see `worst_case_log2_error`.
+///
+/// ## Arguments
+/// * `bit_length` - The bit length of the candidates
+/// * `rounds` - The number of Miller-Rabin rounds
+pub fn miller_rabin_log2_error(bit_length: usize, rounds: usize) -> f64 {
+    let k = bit_length as f64;
+    let t = rounds as f64;
+    let max_m = (2.0 * (k - 1.0).sqrt()) as usize;
+    // Too Small for the Bound
+    if max_m < 4 {
+        return worst_case_log2_error(rounds);
+    }
+    let constant = (8.0 * (PI * PI - 6.0) / 3.0).log2() - 2.0;
+    let leading = (2.00743 * LN_2 * k).log2();
+    let mut terms: Vec<f64> = Vec::new();
+    let mut best = f64::INFINITY;
+    for big_m in 3..max_m {
+        // Inner Double Sum Grows With M
+        for j in 2..=big_m {
+            let m = big_m as f64;
+            let j = j as f64;
+            terms.push(constant + m - (m - 1.0) * t - j - (k - 1.0) / j);
+        }
+        let mut all_terms = terms.clone();
+        all_terms.push(-2.0 - (big_m as f64 - 1.0) * t);
+        best = best.min(leading + log2_sum(&all_terms));
+    }
+    best.min(0.0)
+}
+
+/// log2 of the worst case bound 4^-t, valid for any odd composite
+pub fn worst_case_log2_error(rounds: usize) -> f64 {
+    -2.0 * rounds as f64
+}
+
+/// # Rounds for a Target Error
+/// Smallest number of Miller-Rabin rounds for which a random
+/// `bit_length`-bit candidate is composite with probability at most
+/// 2^-`security_bits`, e.g. 7 rounds for 512 bits at 2^-100 or 4 rounds for
+/// 1536 bits at 2^-128.
+///
+/// ## Arguments
+/// * `bit_length` - The bit length of the candidates
+/// * `security_bits` - The target error probability is 2^-security_bits
+pub fn miller_rabin_rounds(bit_length: usize, security_bits: u32) -> RoundEstimate {
+    let target = -(security_bits as f64);
+    let mut rounds = 1;
+    loop {
+        let log2_error = miller_rabin_log2_error(bit_length, rounds);
+        if log2_error <= target {
+            return RoundEstimate { rounds, log2_error };
+        }
+        rounds += 1;
+    }
+}
+
+/// # Rounds for a Target Error (Worst Case)
+/// Rounds needed when the tested number may be chosen by an adversary, from
+/// the bound 4^-t.
+///
+/// ## Arguments
+/// * `security_bits` - The target error probability is 2^-security_bits
+pub fn miller_rabin_rounds_worst_case(security_bits: u32) -> RoundEstimate {
+    let rounds = security_bits.div_ceil(2).max(1) as usize;
+    RoundEstimate {
+        rounds,
+        log2_error: worst_case_log2_error(rounds),
+    }
+}
+
+#[test]
+fn test_miller_rabin_rounds() {
+    // FIPS 186-5 Table B.1
+    assert_eq!(miller_rabin_rounds(512, 100).rounds, 7);
+    assert_eq!(miller_rabin_rounds(1024, 112).rounds, 5);
+    assert_eq!(miller_rabin_rounds(1536, 128).rounds, 4);
+    // The Bound Holds and One Round Less Misses It
+    for bit_length in [64, 256, 1024, 4096] {
+        for security_bits in [64, 100, 128] {
+            let estimate = miller_rabin_rounds(bit_length, security_bits);
+            assert!(estimate.log2_error <= -(security_bits as f64));
+            if estimate.rounds > 1 {
+                assert!(
+                    miller_rabin_log2_error(bit_length, estimate.rounds - 1)
+                        > -(security_bits as f64)
+                );
+            }
+            assert!(estimate.rounds <= miller_rabin_rounds_worst_case(security_bits).rounds);
+        }
+    }
+    assert_eq!(miller_rabin_rounds_worst_case(128).rounds, 64);
+    assert_eq!(miller_rabin_rounds(3, 20).rounds, 10);
+}
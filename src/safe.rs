@@ -0,0 +1,156 @@
+use num::{BigUint, Integer, ToPrimitive};
+
+use crate::{
+    generator::{GenerationError, Limits, DEFAULT_SECURITY_BITS},
+    prime::{is_prime_miller_rabin, small_odd_primes, SMALL_PRIMES_BOUND},
+    random::RandomGenerator,
+    rounds::miller_rabin_rounds,
+};
+
+/// Whether both q and p = 2q + 1 are (probable) primes
+pub fn is_safe_prime(p: &BigUint, rounds: usize) -> bool {
+    if p.is_even() || *p < BigUint::from(5u32) {
+        return false;
+    }
+    let q = p >> 1;
+    if q == BigUint::from(2u32) {
+        return true;
+    }
+    is_prime_miller_rabin(q, rounds) && is_prime_miller_rabin(p.clone(), rounds)
+}
+
+/// # Safe Prime Generation
+/// Returns p = 2q + 1 with exactly `bits` bits and both p and q prime. The
+/// search walks q ≡ 5 (mod 6) upwards from a random start and keeps the
+/// residues of q modulo the small primes s, skipping any q with s | q or
+/// s | 2q + 1 using machine word arithmetic only. Survivors go through a
+/// base 2 Fermat test on q and on p before the Miller-Rabin rounds on q.
+/// These rounds are enough: if q is prime and 2^(p-1) ≡ 1 (mod p) with
+/// 3 ∤ p, then p is prime (Pocklington with the factor q > √p). Fails on
+/// bit lengths below 3 or once one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `bits` - The bit length of p (at least 3)
+/// * `rng` - The source of random starts
+/// * `limits` - When to give up, counting every q of the walk
+pub fn gen_safe_prime<R: RandomGenerator + ?Sized>(
+    bits: usize,
+    rng: &mut R,
+    limits: &Limits,
+) -> Result<BigUint, GenerationError> {
+    if bits < 3 {
+        return Err(GenerationError::InvalidBitLength(bits));
+    }
+    // Too Small for the Sieve
+    if bits <= 4 {
+        return Ok(BigUint::from(if bits == 3 { 7u32 } else { 11u32 }));
+    }
+    let rounds = miller_rabin_rounds(bits - 1, DEFAULT_SECURITY_BITS).rounds;
+    // Only Sieve With Primes Below q, So q Itself is Never Discarded
+    let small_primes: Vec<u32> = small_odd_primes(SMALL_PRIMES_BOUND)
+        .into_iter()
+        .filter(|&prime| ((32 - prime.leading_zeros()) as usize) < bits - 2)
+        .collect();
+    let two = BigUint::from(2u32);
+    let mut candidates: u64 = 0;
+    loop {
+        // Random Start q With bits - 1 Bits, q ≡ 5 (mod 6)
+        let mut start = rng.gen_bits(bits - 1);
+        start.set_bit(bits as u64 - 2, true);
+        start = &start - &start % 6u32 + 5u32;
+        let residues: Vec<u32> = small_primes
+            .iter()
+            .map(|&prime| (&start % prime).to_u32().unwrap())
+            .collect();
+        // Walk by 6 until the Delta Overflows
+        let mut delta: u32 = 0;
+        while delta <= u32::MAX - SMALL_PRIMES_BOUND - 6 {
+            limits.check(candidates)?;
+            candidates += 1;
+            // Skip When q or 2q + 1 Has a Small Factor
+            let has_small_factor =
+                small_primes
+                    .iter()
+                    .zip(residues.iter())
+                    .any(|(&prime, &residue)| {
+                        let residue = (residue + delta % prime) % prime;
+                        residue == 0 || residue == prime / 2
+                    });
+            delta += 6;
+            if has_small_factor {
+                continue;
+            }
+            let q = &start + delta - 6u32;
+            let p: BigUint = (&q << 1u32) + 1u32;
+            // Draw a New Start if the Walk Left the Bit Length
+            if p.bits() as usize > bits {
+                break;
+            }
+            // Fast Double Test, Then the Full Test on q
+            let q_minus_one = &q - 1u32;
+            if two.modpow(&q_minus_one, &q) != BigUint::from(1u32) {
+                continue;
+            }
+            if two.modpow(&(&q << 1u32), &p) != BigUint::from(1u32) {
+                continue;
+            }
+            if is_prime_miller_rabin(q.clone(), rounds) {
+                return Ok(p);
+            }
+        }
+    }
+}
+
+/// # Sophie Germain Prime Generation
+/// Returns q with exactly `bits` bits such that 2q + 1 is also prime.
+///
+/// ## Arguments
+/// * `bits` - The bit length of q (at least 2)
+/// * `rng` - The source of random starts
+/// * `limits` - When to give up
+pub fn gen_sophie_germain_prime<R: RandomGenerator + ?Sized>(
+    bits: usize,
+    rng: &mut R,
+    limits: &Limits,
+) -> Result<BigUint, GenerationError> {
+    if bits < 2 {
+        return Err(GenerationError::InvalidBitLength(bits));
+    }
+    Ok(gen_safe_prime(bits + 1, rng, limits)? >> 1)
+}
+
+#[test]
+fn test_gen_safe_prime() {
+    use crate::prime::is_prime_bpsw;
+    use crate::random::Lcg;
+    let mut rng = Lcg::new(BigUint::from(99u32));
+    for bits in [3, 4, 5, 6, 8, 16, 64, 256] {
+        let p = gen_safe_prime(bits, &mut rng, &Limits::none()).unwrap();
+        assert_eq!(p.bits(), bits as u64);
+        assert!(is_prime_bpsw(&p) && is_prime_bpsw(&(&p >> 1)), "{}", p);
+        assert!(is_safe_prime(&p, 10));
+    }
+    let q = gen_sophie_germain_prime(128, &mut rng, &Limits::none()).unwrap();
+    assert_eq!(q.bits(), 128);
+    assert!(is_prime_bpsw(&q) && is_prime_bpsw(&((&q << 1u32) + 1u32)));
+    // 23 = 2 * 11 + 1 is Safe, 29 and 13 = 2 * 6 + 1 are not
+    assert!(is_safe_prime(&BigUint::from(23u32), 5));
+    assert!(is_safe_prime(&BigUint::from(5u32), 5));
+    assert!(!is_safe_prime(&BigUint::from(29u32), 5));
+    assert!(!is_safe_prime(&BigUint::from(13u32), 5));
+    // Bad Bit Lengths and Limits Fail Instead of Panicking or Spinning
+    let none = Limits::none();
+    assert_eq!(
+        gen_safe_prime(2, &mut rng, &none),
+        Err(GenerationError::InvalidBitLength(2))
+    );
+    assert_eq!(
+        gen_sophie_germain_prime(1, &mut rng, &none),
+        Err(GenerationError::InvalidBitLength(1))
+    );
+    let limits = Limits::none().max_candidates(10);
+    assert_eq!(
+        gen_safe_prime(1024, &mut rng, &limits),
+        Err(GenerationError::CandidateLimitReached(10))
+    );
+}
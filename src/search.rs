@@ -0,0 +1,246 @@
+use bitvec::prelude::*;
+use num::{BigUint, One, ToPrimitive};
+
+use crate::{
+    counting::{li, prime_count},
+    prime::{is_prime_miller_rabin, is_prime_u64, small_odd_primes, SMALL_PRIMES_BOUND},
+    sieve::{primes_up_to, PrimeIter},
+};
+
+/// Number of odd candidates sieved at once when searching big numbers
+const WINDOW_SIZE: usize = 4096;
+
+/// Miller-Rabin rounds used on the big candidates that survive the sieve
+pub const SEARCH_ROUNDS: usize = 40;
+
+/// Up to this index `nth_prime` sieves directly, above it counts with π(x)
+const NTH_PRIME_SIEVE_LIMIT: u64 = 1_000_000;
+
+/// Searching primes around a number
+pub trait PrimeSearch: Sized {
+    /// The smallest prime greater than `self`, or `None` if it does not fit
+    /// in the type
+    fn next_prime(&self) -> Option<Self>;
+
+    /// The largest prime smaller than `self`, or `None` if `self` ≤ 2
+    fn prev_prime(&self) -> Option<Self>;
+}
+
+impl PrimeSearch for u64 {
+    fn next_prime(&self) -> Option<u64> {
+        if *self < 2 {
+            return Some(2);
+        }
+        let mut candidate = self.checked_add(1)? | 1;
+        while !is_prime_u64(candidate) {
+            candidate = candidate.checked_add(2)?;
+        }
+        Some(candidate)
+    }
+
+    fn prev_prime(&self) -> Option<u64> {
+        match *self {
+            0..=2 => None,
+            3 => Some(2),
+            _ => {
+                let mut candidate = (*self - 2) | 1;
+                while !is_prime_u64(candidate) {
+                    candidate -= 2;
+                }
+                Some(candidate)
+            }
+        }
+    }
+}
+
+impl PrimeSearch for u32 {
+    fn next_prime(&self) -> Option<u32> {
+        (*self as u64).next_prime()?.to_u32()
+    }
+
+    fn prev_prime(&self) -> Option<u32> {
+        (*self as u64).prev_prime().map(|prime| prime as u32)
+    }
+}
+
+impl PrimeSearch for usize {
+    fn next_prime(&self) -> Option<usize> {
+        (*self as u64).next_prime()?.to_usize()
+    }
+
+    fn prev_prime(&self) -> Option<usize> {
+        (*self as u64).prev_prime().map(|prime| prime as usize)
+    }
+}
+
+/// Sieves `WINDOW_SIZE` odd candidates `start ± 2i` (upwards if `up`) with the
+/// small primes. Bit i is cleared when the candidate has a small factor. The
+/// candidates must be greater than the small primes.
+fn sieve_window(start: &BigUint, up: bool, small_primes: &[u32]) -> BitVec<Lsb0, usize> {
+    let mut window: BitVec<Lsb0, usize> = BitVec::repeat(true, WINDOW_SIZE);
+    for &prime in small_primes {
+        // Solve start ± 2i ≡ 0 (mod p) With 1/2 ≡ (p + 1) / 2
+        let residue = (start % prime).to_u64().unwrap();
+        let half = (prime as u64).div_ceil(2);
+        let shift = if up { prime as u64 - residue } else { residue };
+        let mut idx = (shift * half % prime as u64) as usize;
+        while idx < WINDOW_SIZE {
+            window.set(idx, false);
+            idx += prime as usize;
+        }
+    }
+    window
+}
+
+impl PrimeSearch for BigUint {
+    fn next_prime(&self) -> Option<BigUint> {
+        // Machine Integers are Exact and Faster
+        if let Some(prime) = self.to_u64().and_then(|value| value.next_prime()) {
+            return Some(BigUint::from(prime));
+        }
+        let small_primes = small_odd_primes(SMALL_PRIMES_BOUND);
+        let mut start = (self + 1u32) | BigUint::one();
+        loop {
+            let window = sieve_window(&start, true, &small_primes);
+            for idx in window.iter_ones() {
+                let candidate = &start + 2 * idx;
+                if is_prime_miller_rabin(candidate.clone(), SEARCH_ROUNDS) {
+                    return Some(candidate);
+                }
+            }
+            start += 2 * WINDOW_SIZE;
+        }
+    }
+
+    fn prev_prime(&self) -> Option<BigUint> {
+        if let Some(value) = self.to_u64() {
+            return value.prev_prime().map(BigUint::from);
+        }
+        let small_primes = small_odd_primes(SMALL_PRIMES_BOUND);
+        let mut start = (self - 2u32) | BigUint::one();
+        loop {
+            // Finish in the Machine Integers Once Below 2^64
+            if let Some(value) = start.to_u64() {
+                return (value + 2).prev_prime().map(BigUint::from);
+            }
+            let window = sieve_window(&start, false, &small_primes);
+            for idx in window.iter_ones() {
+                let candidate = &start - 2 * idx;
+                if is_prime_miller_rabin(candidate.clone(), SEARCH_ROUNDS) {
+                    return Some(candidate);
+                }
+            }
+            start -= 2 * WINDOW_SIZE;
+        }
+    }
+}
+
+/// Computes the smallest prime greater than `n`
+pub fn next_prime<T: PrimeSearch>(n: &T) -> Option<T> {
+    n.next_prime()
+}
+
+/// Computes the largest prime smaller than `n`
+pub fn prev_prime<T: PrimeSearch>(n: &T) -> Option<T> {
+    n.prev_prime()
+}
+
+/// # N-th Prime
+/// Computes the k-th prime (`nth_prime(1)` = 2). Small indices sieve up to the
+/// bound p_k < k (ln k + ln ln k). Large indices invert li(x) to estimate p_k,
+/// count the primes below the estimate with `prime_count` and sieve the
+/// remaining gap.
+///
+/// ## Arguments
+/// * `k` - The index of the prime (starting at 1)
+pub fn nth_prime(k: u64) -> u64 {
+    assert!(k > 0, "the primes are indexed from 1");
+    if k <= NTH_PRIME_SIEVE_LIMIT {
+        let k_float = (k as f64).max(6.0);
+        let bound = (k_float * (k_float.ln() + k_float.ln().ln())) as u64;
+        return primes_up_to(bound)[k as usize - 1];
+    }
+    // Solve li(x) = k With Newton's Method (li'(x) = 1 / ln x)
+    let k_float = k as f64;
+    let mut estimate = k_float * k_float.ln();
+    for _ in 0..100 {
+        let step = (li(estimate) - k_float) * estimate.ln();
+        estimate -= step;
+        if step.abs() < 1.0 {
+            break;
+        }
+    }
+    // Start Below the Estimate, Moving Down if it Overshoots
+    let mut margin = (estimate.sqrt() * estimate.ln()) as u64;
+    let (start, count) = loop {
+        let start = (estimate as u64).saturating_sub(margin);
+        let count = prime_count(start);
+        if count < k {
+            break (start, count);
+        }
+        margin *= 2;
+    };
+    // Sieve the Gap
+    PrimeIter::starting_at(start + 1)
+        .nth((k - count - 1) as usize)
+        .unwrap()
+}
+
+#[test]
+fn test_next_and_prev_prime_u64() {
+    assert_eq!(next_prime(&0u64), Some(2));
+    assert_eq!(next_prime(&2u64), Some(3));
+    assert_eq!(next_prime(&13u64), Some(17));
+    assert_eq!(next_prime(&1_000_000_000u64), Some(1_000_000_007));
+    assert_eq!(next_prime(&18446744073709551557u64), None);
+    assert_eq!(prev_prime(&2u64), None);
+    assert_eq!(prev_prime(&3u64), Some(2));
+    assert_eq!(prev_prime(&17u64), Some(13));
+    assert_eq!(prev_prime(&u64::MAX), Some(18446744073709551557));
+    assert_eq!(next_prime(&4294967291u32), None);
+    assert_eq!(prev_prime(&100usize), Some(97));
+}
+
+#[test]
+fn test_next_and_prev_prime_biguint() {
+    use std::str::FromStr;
+    // 2^64 + 13 is the First Prime After 2^64
+    let two_64 = BigUint::from(u64::MAX) + 1u32;
+    assert_eq!(
+        next_prime(&BigUint::from(u64::MAX - 1)),
+        Some(two_64.clone() + 13u32)
+    );
+    assert_eq!(
+        prev_prime(&(two_64.clone() + 13u32)),
+        Some(BigUint::from(18446744073709551557u64))
+    );
+    // 2^127 - 1 is a Mersenne Prime
+    let mersenne = BigUint::from_str("170141183460469231731687303715884105727").unwrap();
+    assert_eq!(
+        next_prime(&(mersenne.clone() - 2u32)),
+        Some(mersenne.clone())
+    );
+    assert_eq!(prev_prime(&(mersenne.clone() + 2u32)), Some(mersenne));
+}
+
+#[test]
+fn test_nth_prime() {
+    assert_eq!(nth_prime(1), 2);
+    assert_eq!(nth_prime(2), 3);
+    assert_eq!(nth_prime(6), 13);
+    assert_eq!(nth_prime(1_000), 7919);
+    assert_eq!(nth_prime(1_000_000), 15485863);
+    assert_eq!(nth_prime(1_000_001), 15485867);
+    assert_eq!(nth_prime(50_847_534), 999999937);
+}
+
+#[test]
+fn test_next_prime_at_u64_max() {
+    // No u64 Prime Above the Largest One, and no Overflow
+    assert_eq!(u64::MAX.next_prime(), None);
+    assert_eq!((u64::MAX - 1).next_prime(), None);
+    assert_eq!(18446744073709551556u64.next_prime(), Some(18446744073709551557));
+    // BigUint Leaves the Fast Path: the Next Prime is 2^64 + 13
+    let expected = (BigUint::one() << 64u32) + 13u32;
+    assert_eq!(BigUint::from(u64::MAX).next_prime(), Some(expected));
+}
@@ -0,0 +1,204 @@
+use num::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    generator::{GenerationError, Limits, DEFAULT_SECURITY_BITS},
+    prime::{is_prime_bpsw, is_prime_miller_rabin_seeded},
+    provable::Seed,
+    random::RandomGenerator,
+    rounds::miller_rabin_rounds,
+};
+
+/// (L, N) pairs allowed by FIPS 186-4 Section 4.2
+pub const APPROVED_LENGTHS: [(usize, usize); 4] =
+    [(1024, 160), (2048, 224), (2048, 256), (3072, 256)];
+
+/// Primes p and q (q | p - 1) with the seed and counter that derive them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainParameters {
+    /// The L-bit prime modulus
+    pub p: BigUint,
+    /// The N-bit prime divisor of p - 1
+    pub q: BigUint,
+    /// The published seed
+    pub domain_parameter_seed: Vec<u8>,
+    /// The index of p among the candidates derived from the seed
+    pub counter: usize,
+}
+
+/// Primality test of the derivation; BPSW plus Miller-Rabin rounds with bases
+/// drawn from the domain parameter seed, so the verdict (and thus the derived
+/// primes) is the same on every run
+fn is_prime_seeded(value: &BigUint, seed: &Seed) -> bool {
+    let rounds = miller_rabin_rounds(value.bits() as usize, DEFAULT_SECURITY_BITS).rounds;
+    let bases_seed = BigUint::from_bytes_be(&seed.bytes(0));
+    is_prime_bpsw(value) && is_prime_miller_rabin_seeded(value.clone(), rounds, bases_seed)
+}
+
+/// Steps 6 - 7 of A.1.1.2: q = 2^(N-1) + U + 1 - (U mod 2) with U = Hash(seed) mod 2^(N-1)
+fn derive_q<D: Digest>(seed: &Seed, n: usize) -> BigUint {
+    let top = BigUint::from(1u32) << (n - 1);
+    let u = seed.hash::<D>(0) % &top;
+    let parity = if u.bit(0) { 1u32 } else { 0u32 };
+    top + u + 1u32 - parity
+}
+
+/// Steps 11.1 - 11.5 of A.1.1.2: the candidate p at `offset`
+fn derive_p<D: Digest>(seed: &Seed, offset: usize, l: usize, q: &BigUint) -> BigUint {
+    let outlen = <D as Digest>::output_size() * 8;
+    let n = l.div_ceil(outlen) - 1;
+    let b = l - 1 - n * outlen;
+    let mut w = BigUint::from(0u32);
+    for j in 0..=n {
+        let mut v = seed.hash::<D>(offset + j);
+        if j == n {
+            v %= BigUint::from(1u32) << b;
+        }
+        w += v << (j * outlen);
+    }
+    let x = w + (BigUint::from(1u32) << (l - 1));
+    let c = &x % (q * 2u32);
+    x - (c - 1u32)
+}
+
+/// Checks (L, N) and the seed length
+fn check_lengths(l: usize, n: usize, seed: &[u8]) -> Result<(), GenerationError> {
+    if !APPROVED_LENGTHS.contains(&(l, n)) {
+        return Err(GenerationError::InvalidBitLength(l));
+    }
+    if seed.len() * 8 < n {
+        return Err(GenerationError::InvalidBitLength(seed.len() * 8));
+    }
+    Ok(())
+}
+
+/// # Seeded Domain Parameter Generation
+/// FIPS 186-4 A.1.1.2 with the hash `D`: q is derived from a seed of
+/// `seed_bytes` bytes drawn from `rng` (a new one while q is composite) and
+/// p = X - (X mod 2q - 1) from hashes of seed + offset, for counter = 0, 1,
+/// ... up to 4L - 1. The returned seed and counter let anyone re-derive p
+/// and q with `verify_domain_parameters`. Fails on lengths FIPS 186-4 does
+/// not approve or once one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `l` - The bit length of p
+/// * `n` - The bit length of q
+/// * `seed_bytes` - The length of the seed (at least N bits)
+/// * `rng` - The source of seeds
+/// * `limits` - When to give up, counting every candidate for q and p
+pub fn gen_domain_parameters<D: Digest, R: RandomGenerator + ?Sized>(
+    l: usize,
+    n: usize,
+    seed_bytes: usize,
+    rng: &mut R,
+    limits: &Limits,
+) -> Result<DomainParameters, GenerationError> {
+    let outlen = <D as Digest>::output_size() * 8;
+    if n > outlen {
+        return Err(GenerationError::InvalidBitLength(n));
+    }
+    let outer_blocks = l.div_ceil(outlen);
+    let mut candidates: u64 = 0;
+    loop {
+        limits.check(candidates)?;
+        candidates += 1;
+        // Steps 5 - 9: Find a Seed Giving a Prime q
+        let raw = rng.gen_bits(seed_bytes * 8).to_bytes_be();
+        let mut domain_parameter_seed = vec![0u8; seed_bytes.saturating_sub(raw.len())];
+        domain_parameter_seed.extend_from_slice(&raw);
+        check_lengths(l, n, &domain_parameter_seed)?;
+        let seed = Seed::new(&domain_parameter_seed);
+        let q = derive_q::<D>(&seed, n);
+        if !is_prime_seeded(&q, &seed) {
+            continue;
+        }
+        // Steps 10 - 11: Walk the Candidates for p
+        let low = BigUint::from(1u32) << (l - 1);
+        for counter in 0..4 * l {
+            limits.check(candidates)?;
+            candidates += 1;
+            let p = derive_p::<D>(&seed, 1 + counter * outer_blocks, l, &q);
+            if p >= low && is_prime_seeded(&p, &seed) {
+                return Ok(DomainParameters {
+                    p,
+                    q,
+                    domain_parameter_seed,
+                    counter,
+                });
+            }
+        }
+    }
+}
+
+/// # Seeded Domain Parameter Validation
+/// FIPS 186-4 A.1.1.3 with the hash `D`: re-derives q from the seed and the
+/// candidates for p up to the counter, and accepts only if they match and
+/// p is the first prime candidate.
+///
+/// ## Arguments
+/// * `parameters` - The published p, q, seed and counter
+pub fn verify_domain_parameters<D: Digest>(parameters: &DomainParameters) -> bool {
+    let DomainParameters {
+        p,
+        q,
+        domain_parameter_seed,
+        counter,
+    } = parameters;
+    let (l, n) = (p.bits() as usize, q.bits() as usize);
+    if check_lengths(l, n, domain_parameter_seed).is_err() || *counter >= 4 * l {
+        return false;
+    }
+    let seed = Seed::new(domain_parameter_seed);
+    if derive_q::<D>(&seed, n) != *q || !is_prime_seeded(q, &seed) {
+        return false;
+    }
+    let outer_blocks = l.div_ceil(<D as Digest>::output_size() * 8);
+    let low = BigUint::from(1u32) << (l - 1);
+    for i in 0..=*counter {
+        let candidate = derive_p::<D>(&seed, 1 + i * outer_blocks, l, q);
+        if candidate >= low && is_prime_seeded(&candidate, &seed) {
+            return i == *counter && candidate == *p;
+        }
+    }
+    false
+}
+
+/// `gen_domain_parameters` with SHA-256 and a 256-bit seed
+pub fn gen_domain_parameters_sha256<R: RandomGenerator + ?Sized>(
+    l: usize,
+    n: usize,
+    rng: &mut R,
+    limits: &Limits,
+) -> Result<DomainParameters, GenerationError> {
+    gen_domain_parameters::<Sha256, R>(l, n, 32, rng, limits)
+}
+
+#[test]
+fn test_seeded_domain_parameters() {
+    use crate::random::Lcg;
+    let mut rng = Lcg::new(BigUint::from(186u32));
+    let parameters = gen_domain_parameters_sha256(1024, 160, &mut rng, &Limits::none()).unwrap();
+    assert_eq!(parameters.p.bits(), 1024);
+    assert_eq!(parameters.q.bits(), 160);
+    assert_eq!((&parameters.p - 1u32) % &parameters.q, BigUint::from(0u32));
+    assert!(verify_domain_parameters::<Sha256>(&parameters));
+    // Any Change is Detected
+    let mut tampered = parameters.clone();
+    tampered.counter += 1;
+    assert!(!verify_domain_parameters::<Sha256>(&tampered));
+    let mut tampered = parameters.clone();
+    tampered.domain_parameter_seed[0] ^= 1;
+    assert!(!verify_domain_parameters::<Sha256>(&tampered));
+    let mut tampered = parameters;
+    tampered.p += &tampered.q * 2u32;
+    assert!(!verify_domain_parameters::<Sha256>(&tampered));
+    assert_eq!(
+        gen_domain_parameters_sha256(512, 160, &mut rng, &Limits::none()),
+        Err(GenerationError::InvalidBitLength(512))
+    );
+    let limits = Limits::none().max_candidates(5);
+    assert_eq!(
+        gen_domain_parameters_sha256(1024, 160, &mut rng, &limits),
+        Err(GenerationError::CandidateLimitReached(5))
+    );
+}
@@ -0,0 +1,606 @@
+use bitvec::prelude::*;
+use num::integer::Roots;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Number of odd values covered by a single segment (32 KiB of bits, so a
+/// segment fits in the L1 cache of most processors)
+pub const SEGMENT_SIZE: usize = 1 << 18;
+
+/// Computes the odd primes ≤ `limit` with a plain (non segmented) odd-only
+/// sieve. These are the sieving primes of the segments.
+pub(crate) fn base_primes(limit: u64) -> Vec<u64> {
+    if limit < 3 {
+        return Vec::new();
+    }
+    // Bit i Represents the Value 2i + 1
+    let size = ((limit - 1) / 2 + 1) as usize;
+    let mut is_prime: BitVec<Lsb0, usize> = BitVec::repeat(true, size);
+    is_prime.set(0, false);
+    let mut idx = 1;
+    while (2 * idx + 1) * (2 * idx + 1) < 2 * size + 1 {
+        if is_prime[idx] {
+            // Start Crossing at p^2
+            let prime = 2 * idx + 1;
+            let mut multiple = (prime * prime - 1) / 2;
+            while multiple < size {
+                is_prime.set(multiple, false);
+                multiple += prime;
+            }
+        }
+        idx += 1;
+    }
+    is_prime.iter_ones().map(|idx| 2 * idx as u64 + 1).collect()
+}
+
+/// Sieves `len` consecutive odd values starting at the odd value `low`. Bit i
+/// of the result is set when `low + 2i` is prime. The `base_primes` must
+/// contain every odd prime ≤ √(low + 2 * (len - 1)).
+pub(crate) fn sieve_segment(low: u64, len: usize, base_primes: &[u64]) -> BitVec<Lsb0, usize> {
+    let high = low + 2 * (len as u64 - 1);
+    let mut segment: BitVec<Lsb0, usize> = BitVec::repeat(true, len);
+    // One is not a Prime
+    if low == 1 {
+        segment.set(0, false);
+    }
+    for &prime in base_primes {
+        if prime * prime > high {
+            break;
+        }
+        // Find the First Odd Multiple of the Prime Inside the Segment (≥ p^2),
+        // None of Them Fits in a u64 Near the Top
+        let multiple = match low.div_ceil(prime).checked_mul(prime) {
+            Some(multiple) => multiple,
+            None => continue,
+        };
+        let mut first = (prime * prime).max(multiple);
+        if first % 2 == 0 {
+            first = match first.checked_add(prime) {
+                Some(first) => first,
+                None => continue,
+            };
+        }
+        if first > high {
+            continue;
+        }
+        let mut idx = ((first - low) / 2) as usize;
+        while idx < len {
+            segment.set(idx, false);
+            idx += prime as usize;
+        }
+    }
+    segment
+}
+
+/// # Segmented Sieve of Eratosthenes
+/// Computes every prime in the interval [a, b] (both inclusive). Only odd values
+/// are stored and the interval is processed in segments of `SEGMENT_SIZE` odd
+/// values, so the memory usage is O(√b + SEGMENT_SIZE) regardless of the
+/// interval width.
+///
+/// ## Arguments
+/// * `a` - The lower bound of the interval
+/// * `b` - The upper bound of the interval
+pub fn primes_in_range(a: u64, b: u64) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::new();
+    if b < 2 || a > b {
+        return primes;
+    }
+    // Two is the Only Even Prime
+    if a <= 2 {
+        primes.push(2);
+    }
+    // Sieve the Odd Values in Segments
+    let base_primes = base_primes(b.sqrt());
+    let mut low = a.max(3) | 1;
+    while low <= b {
+        let len = (((b - low) / 2 + 1) as usize).min(SEGMENT_SIZE);
+        let segment = sieve_segment(low, len, &base_primes);
+        primes.extend(segment.iter_ones().map(|idx| low + 2 * idx as u64));
+        // Stop Instead of Overflowing When b is Near u64::MAX
+        low = match low.checked_add(2 * len as u64) {
+            Some(low) => low,
+            None => break,
+        };
+    }
+    primes
+}
+
+/// Computes every prime ≤ `n`
+pub fn primes_up_to(n: u64) -> Vec<u64> {
+    primes_in_range(0, n)
+}
+
+/// Splits the odd values of [a, b] into segments and hands them to `threads`
+/// workers, which take the next unprocessed segment until none is left. The
+/// result of `process` for each segment is returned in increasing order of the
+/// segments.
+fn sieve_parallel<T, F>(a: u64, b: u64, threads: usize, process: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(u64, &BitVec<Lsb0, usize>) -> T + Sync,
+{
+    let low = a.max(3) | 1;
+    if b < low {
+        return Vec::new();
+    }
+    let odd_values = (b - low) / 2 + 1;
+    let segments = odd_values.div_ceil(SEGMENT_SIZE as u64) as usize;
+    let base_primes = base_primes(b.sqrt());
+    let next_segment = AtomicUsize::new(0);
+    // Sieve the Segments in the Workers
+    let mut results: Vec<(usize, T)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let idx = next_segment.fetch_add(1, Ordering::Relaxed);
+                        if idx >= segments {
+                            return results;
+                        }
+                        let offset = idx as u64 * SEGMENT_SIZE as u64;
+                        let segment_low = low + 2 * offset;
+                        let len = (odd_values - offset).min(SEGMENT_SIZE as u64) as usize;
+                        let segment = sieve_segment(segment_low, len, &base_primes);
+                        results.push((idx, process(segment_low, &segment)));
+                    }
+                })
+            })
+            .collect();
+        // Wait Threads
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    // Merge in Order
+    results.sort_unstable_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// # Parallel Segmented Sieve of Eratosthenes
+/// Computes every prime in the interval [a, b] (both inclusive), sieving the
+/// segments in `threads` worker threads. The output is ordered.
+///
+/// ## Arguments
+/// * `a` - The lower bound of the interval
+/// * `b` - The upper bound of the interval
+/// * `threads` - The number of worker threads
+pub fn primes_in_range_parallel(a: u64, b: u64, threads: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::new();
+    if b < 2 || a > b {
+        return primes;
+    }
+    // Two is the Only Even Prime
+    if a <= 2 {
+        primes.push(2);
+    }
+    let segments = sieve_parallel(a, b, threads, |low, segment| {
+        segment
+            .iter_ones()
+            .map(|idx| low + 2 * idx as u64)
+            .collect::<Vec<u64>>()
+    });
+    primes.extend(segments.into_iter().flatten());
+    primes
+}
+
+/// # Parallel Prime Count
+/// Counts the primes in the interval [a, b] (both inclusive) without storing
+/// them, sieving the segments in `threads` worker threads.
+///
+/// ## Arguments
+/// * `a` - The lower bound of the interval
+/// * `b` - The upper bound of the interval
+/// * `threads` - The number of worker threads
+pub fn prime_count_in_range_parallel(a: u64, b: u64, threads: usize) -> u64 {
+    if b < 2 || a > b {
+        return 0;
+    }
+    let two = if a <= 2 { 1 } else { 0 };
+    let counts = sieve_parallel(a, b, threads, |_, segment| segment.count_ones() as u64);
+    two + counts.into_iter().sum::<u64>()
+}
+
+/// # Prime Iterator
+/// Lazily yields the primes in increasing order, sieving one segment at a time.
+/// The sieving primes are extended on demand as the segments move upwards.
+pub struct PrimeIter {
+    base_primes: Vec<u64>,
+    base_limit: u64,
+    low: u64,
+    segment: Vec<u64>,
+    position: usize,
+    pending_two: bool,
+    exhausted: bool,
+}
+
+impl PrimeIter {
+    /// Creates an iterator over the primes ≥ `start`
+    pub fn starting_at(start: u64) -> PrimeIter {
+        PrimeIter {
+            base_primes: Vec::new(),
+            base_limit: 0,
+            low: start.max(3) | 1,
+            segment: Vec::new(),
+            position: 0,
+            pending_two: start <= 2,
+            exhausted: false,
+        }
+    }
+
+    /// Sieves the next segment of odd values, the last one ends at u64::MAX
+    fn next_segment(&mut self) {
+        let len = ((u64::MAX - self.low) / 2 + 1).min(SEGMENT_SIZE as u64) as usize;
+        let high = self.low + 2 * (len as u64 - 1);
+        // Extend the Sieving Primes if the Segment Needs Them
+        if self.base_limit < high.sqrt() {
+            self.base_limit = (2 * self.base_limit).max(high.sqrt());
+            self.base_primes = base_primes(self.base_limit);
+        }
+        let segment = sieve_segment(self.low, len, &self.base_primes);
+        let low = self.low;
+        self.segment = segment
+            .iter_ones()
+            .map(|idx| low + 2 * idx as u64)
+            .collect();
+        self.position = 0;
+        match high.checked_add(2) {
+            Some(low) => self.low = low,
+            None => self.exhausted = true,
+        }
+    }
+}
+
+impl Iterator for PrimeIter {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.pending_two {
+            self.pending_two = false;
+            return Some(2);
+        }
+        while self.position == self.segment.len() {
+            if self.exhausted {
+                return None;
+            }
+            self.next_segment();
+        }
+        self.position += 1;
+        Some(self.segment[self.position - 1])
+    }
+}
+
+/// Creates a lazy iterator over all the primes
+pub fn primes() -> PrimeIter {
+    PrimeIter::starting_at(0)
+}
+
+/// Common interface of the prime sieves
+pub trait Sieve {
+    /// The name of the sieve (used by the benchmarks)
+    fn name(&self) -> &'static str;
+
+    /// Computes every prime in the interval [a, b] (both inclusive)
+    fn primes_in_range(&self, a: u64, b: u64) -> Vec<u64>;
+
+    /// Computes every prime ≤ `n`
+    fn primes_up_to(&self, n: u64) -> Vec<u64> {
+        self.primes_in_range(0, n)
+    }
+}
+
+/// Segmented odd-only Sieve of Eratosthenes
+pub struct Eratosthenes;
+
+impl Sieve for Eratosthenes {
+    fn name(&self) -> &'static str {
+        "Eratosthenes"
+    }
+
+    fn primes_in_range(&self, a: u64, b: u64) -> Vec<u64> {
+        primes_in_range(a, b)
+    }
+}
+
+/// # Sieve of Atkin
+/// Marks the values of the interval with an odd number of representations by
+/// the quadratic forms 4x² + y² (n mod 12 ∈ {1, 5}), 3x² + y² (n mod 12 = 7)
+/// and 3x² - y² with x > y (n mod 12 = 11), then removes the multiples of the
+/// squares of primes. The enumeration of (x, y) is O(b), so it is best suited
+/// for intervals starting near zero.
+pub struct Atkin;
+
+impl Atkin {
+    /// Flips the flag of `value` if it is inside [a, b]
+    fn flip(flags: &mut BitVec<Lsb0, usize>, a: u64, b: u64, value: u64) {
+        if value >= a && value <= b {
+            let idx = (value - a) as usize;
+            let flag = flags[idx];
+            flags.set(idx, !flag);
+        }
+    }
+}
+
+impl Sieve for Atkin {
+    fn name(&self) -> &'static str {
+        "Atkin"
+    }
+
+    fn primes_in_range(&self, a: u64, b: u64) -> Vec<u64> {
+        let mut primes: Vec<u64> = [2, 3]
+            .iter()
+            .copied()
+            .filter(|&prime| prime >= a && prime <= b)
+            .collect();
+        if b < 5 || a > b {
+            return primes;
+        }
+        let a = a.max(5);
+        let mut flags: BitVec<Lsb0, usize> = BitVec::repeat(false, (b - a + 1) as usize);
+        // Enumerate 4x² + y²
+        let mut x: u64 = 1;
+        while 4 * x * x < b {
+            let base = 4 * x * x;
+            let mut y: u64 = if a > base { (a - base).sqrt() } else { 1 }.max(1);
+            while base + y * y <= b {
+                let value = base + y * y;
+                if value % 12 == 1 || value % 12 == 5 {
+                    Atkin::flip(&mut flags, a, b, value);
+                }
+                y += 1;
+            }
+            x += 1;
+        }
+        // Enumerate 3x² + y²
+        let mut x: u64 = 1;
+        while 3 * x * x < b {
+            let base = 3 * x * x;
+            let mut y: u64 = if a > base { (a - base).sqrt() } else { 1 }.max(1);
+            while base + y * y <= b {
+                let value = base + y * y;
+                if value % 12 == 7 {
+                    Atkin::flip(&mut flags, a, b, value);
+                }
+                y += 1;
+            }
+            x += 1;
+        }
+        // Enumerate 3x² - y² (x > y)
+        let mut x: u64 = 2;
+        while 2 * x * x + 2 * x - 1 <= b {
+            let base = 3 * x * x;
+            let mut y: u64 = if base > b { (base - b).sqrt() } else { 1 }.max(1);
+            while y < x && base - y * y >= a {
+                let value = base - y * y;
+                if value % 12 == 11 {
+                    Atkin::flip(&mut flags, a, b, value);
+                }
+                y += 1;
+            }
+            x += 1;
+        }
+        // Remove the Multiples of the Squares of Primes
+        for prime in base_primes(b.sqrt()).into_iter().filter(|&p| p >= 5) {
+            let square = prime * prime;
+            let mut multiple = a.div_ceil(square) * square;
+            while multiple <= b {
+                flags.set((multiple - a) as usize, false);
+                multiple += square;
+            }
+        }
+        primes.extend(flags.iter_ones().map(|idx| a + idx as u64));
+        primes
+    }
+}
+
+/// Residues modulo 30 that are co-prime with 30
+const WHEEL_RESIDUES: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// # Mod-30 Wheel Sieve
+/// Segmented Sieve of Eratosthenes that only stores the values co-prime with
+/// 2, 3 and 5 (8 of every 30 values, one byte per turn of the wheel). Each
+/// sieving prime p crosses 8 progressions of step 30p, one per residue class
+/// of its multiples.
+pub struct Wheel30;
+
+impl Wheel30 {
+    /// Sieves the turns [`first_turn`, `first_turn` + `turns`) of the wheel.
+    /// Bit 8k + i of the result is set when 30(`first_turn` + k) + R[i] is prime.
+    fn sieve_turns(first_turn: u64, turns: u64, base_primes: &[u64]) -> BitVec<Lsb0, usize> {
+        let mut segment: BitVec<Lsb0, usize> = BitVec::repeat(true, 8 * turns as usize);
+        // One is not a Prime
+        if first_turn == 0 {
+            segment.set(0, false);
+        }
+        let low = 30 * first_turn;
+        let high = 30 * (first_turn + turns);
+        for &prime in base_primes.iter().filter(|&&p| p >= 7) {
+            if prime * prime >= high {
+                break;
+            }
+            for &residue in WHEEL_RESIDUES.iter() {
+                // Find the First Multiple p * m ≥ max(p², low) With m ≡ residue (mod 30)
+                let min_factor = prime.max(low.div_ceil(prime));
+                let mut factor = min_factor - min_factor % 30 + residue;
+                if factor < min_factor {
+                    factor += 30;
+                }
+                let multiple = prime * factor;
+                let idx = WHEEL_RESIDUES
+                    .iter()
+                    .position(|&r| r == multiple % 30)
+                    .unwrap();
+                // Cross the Progression of Step 30p
+                let mut turn = multiple / 30 - first_turn;
+                while turn < turns {
+                    segment.set(8 * turn as usize + idx, false);
+                    turn += prime;
+                }
+            }
+        }
+        segment
+    }
+}
+
+impl Sieve for Wheel30 {
+    fn name(&self) -> &'static str {
+        "Wheel30"
+    }
+
+    fn primes_in_range(&self, a: u64, b: u64) -> Vec<u64> {
+        let mut primes: Vec<u64> = [2, 3, 5]
+            .iter()
+            .copied()
+            .filter(|&prime| prime >= a && prime <= b)
+            .collect();
+        if b < 7 || a > b {
+            return primes;
+        }
+        // Sieve the Wheel Turns in Segments
+        let base_primes = base_primes(b.sqrt());
+        let segment_turns = (SEGMENT_SIZE / 8) as u64;
+        let last_turn = b / 30;
+        let mut first_turn = a / 30;
+        while first_turn <= last_turn {
+            let turns = (last_turn - first_turn + 1).min(segment_turns);
+            let segment = Wheel30::sieve_turns(first_turn, turns, &base_primes);
+            primes.extend(
+                segment
+                    .iter_ones()
+                    .map(|idx| 30 * (first_turn + idx as u64 / 8) + WHEEL_RESIDUES[idx % 8])
+                    .filter(|&value| value >= a && value <= b),
+            );
+            first_turn += turns;
+        }
+        primes
+    }
+}
+
+#[test]
+fn test_primes_up_to_small() {
+    assert_eq!(primes_up_to(0), Vec::<u64>::new());
+    assert_eq!(primes_up_to(2), vec![2]);
+    assert_eq!(
+        primes_up_to(50),
+        vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]
+    );
+}
+
+#[test]
+fn test_primes_up_to_10m() {
+    // π(10^7) = 664579
+    let primes = primes_up_to(10_000_000);
+    assert_eq!(primes.len(), 664579);
+    assert_eq!(*primes.last().unwrap(), 9999991);
+}
+
+#[test]
+fn test_primes_in_range() {
+    assert_eq!(primes_in_range(14, 16), Vec::<u64>::new());
+    assert_eq!(primes_in_range(1, 10), vec![2, 3, 5, 7]);
+    assert_eq!(
+        primes_in_range(1_000_000_000, 1_000_000_100),
+        vec![1000000007, 1000000009, 1000000021, 1000000033, 1000000087, 1000000093, 1000000097]
+    );
+    // Segments Must Agree With a Single Sieve
+    let all = primes_up_to(3_000_000);
+    let range: Vec<u64> = all.iter().copied().filter(|&p| p >= 1_234_567).collect();
+    assert_eq!(primes_in_range(1_234_567, 3_000_000), range);
+}
+
+#[test]
+fn test_sieve_segment_at_u64_max() {
+    // The Multiples of Most Primes Past the Segment Do Not Fit in a u64
+    let low = u64::MAX - 100;
+    let segment = sieve_segment(low, 51, &base_primes(1000));
+    let survivors: Vec<u64> = segment
+        .iter_ones()
+        .map(|idx| u64::MAX - (low + 2 * idx as u64))
+        .collect();
+    assert_eq!(survivors, vec![94, 88, 86, 82, 58, 44, 38]);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, ignore)]
+fn test_sieves_at_u64_max() {
+    // Sieving Primes up to 2^32 Make This Slow Without Optimizations
+    // The Last Primes Below 2^64 are 2^64 - 59, 2^64 - 83 and 2^64 - 95
+    let expected = vec![u64::MAX - 94, u64::MAX - 82, u64::MAX - 58];
+    assert_eq!(primes_in_range(u64::MAX - 100, u64::MAX), expected);
+    assert_eq!(primes_in_range_parallel(u64::MAX - 100, u64::MAX, 2), expected);
+    let iterated: Vec<u64> = PrimeIter::starting_at(u64::MAX - 100).collect();
+    assert_eq!(iterated, expected);
+}
+
+#[test]
+fn test_prime_iter() {
+    let primes_iter: Vec<u64> = primes().take_while(|&p| p <= 3_000_000).collect();
+    assert_eq!(primes_iter, primes_up_to(3_000_000));
+    let after: Vec<u64> = PrimeIter::starting_at(1_000_000_000).take(3).collect();
+    assert_eq!(after, vec![1000000007, 1000000009, 1000000021]);
+}
+
+#[test]
+fn test_sieve_is_ground_truth_for_miller_rabin() {
+    use crate::prime::is_prime_miller_rabin;
+    use num::BigUint;
+    let primes = primes_up_to(20_000);
+    for value in 2..20_000u64 {
+        let is_prime = is_prime_miller_rabin(BigUint::from(value), 20);
+        assert_eq!(is_prime, primes.binary_search(&value).is_ok(), "{}", value);
+    }
+}
+
+#[test]
+fn test_primes_in_range_parallel() {
+    assert_eq!(primes_in_range_parallel(0, 50, 4), primes_up_to(50));
+    assert_eq!(primes_in_range_parallel(14, 16, 4), Vec::<u64>::new());
+    // Many Segments Must be Merged in Order
+    let (a, b) = (1_000_000_000_000, 1_000_000_000_000 + 5_000_000);
+    assert_eq!(primes_in_range_parallel(a, b, 4), primes_in_range(a, b));
+}
+
+#[test]
+fn test_prime_count_in_range_parallel() {
+    // π(10^8) = 5761455
+    assert_eq!(prime_count_in_range_parallel(0, 100_000_000, 4), 5761455);
+    assert_eq!(prime_count_in_range_parallel(3, 3, 2), 1);
+    assert_eq!(prime_count_in_range_parallel(0, 1, 2), 0);
+}
+
+#[test]
+fn test_sieves_agree_with_eratosthenes() {
+    let sieves: Vec<Box<dyn Sieve>> = vec![Box::new(Atkin), Box::new(Wheel30)];
+    for sieve in sieves {
+        // Every Small Interval
+        for a in 0..70 {
+            for b in a..70 {
+                assert_eq!(
+                    sieve.primes_in_range(a, b),
+                    primes_in_range(a, b),
+                    "{} [{}, {}]",
+                    sieve.name(),
+                    a,
+                    b
+                );
+            }
+        }
+        // Multiple Segments
+        assert_eq!(
+            sieve.primes_up_to(2_000_000),
+            primes_up_to(2_000_000),
+            "{}",
+            sieve.name()
+        );
+        assert_eq!(
+            sieve.primes_in_range(999_983, 1_100_003),
+            primes_in_range(999_983, 1_100_003),
+            "{}",
+            sieve.name()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use num::{BigUint, Integer, One, Zero};
+
+use crate::{
+    mersenne::is_mersenne_prime,
+    prime::{is_prime_miller_rabin, is_prime_u64, jacobi_symbol},
+};
+
+/// Largest P tried when looking for the Lucas parameter of the LLR test
+const LLR_PARAMETER_LIMIT: u32 = 100_000;
+
+/// Shapes with a fast deterministic primality test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecialForm {
+    /// 2^p - 1
+    Mersenne(u64),
+    /// 2^(2^m) + 1
+    Fermat(u32),
+    /// k * 2^n + 1 with k odd and k < 2^n
+    Proth { k: BigUint, n: u64 },
+    /// k * 2^n - 1 with k odd and k < 2^n
+    Riesel { k: BigUint, n: u64 },
+    /// Anything else
+    General,
+}
+
+impl SpecialForm {
+    /// Recognizes the form of `value`
+    pub fn detect(value: &BigUint) -> SpecialForm {
+        if value.is_even() || value.is_one() {
+            return SpecialForm::General;
+        }
+        // value + 1 = k * 2^n
+        let plus_one = value + 1u32;
+        let n = plus_one.trailing_zeros().unwrap();
+        let k = &plus_one >> n;
+        if k.is_one() {
+            return SpecialForm::Mersenne(n);
+        }
+        if k.bits() <= n {
+            return SpecialForm::Riesel { k, n };
+        }
+        // value - 1 = k * 2^n
+        let minus_one = value - 1u32;
+        let n = minus_one.trailing_zeros().unwrap();
+        let k = &minus_one >> n;
+        if k.is_one() && n.is_power_of_two() {
+            return SpecialForm::Fermat(n.trailing_zeros());
+        }
+        if k.bits() <= n {
+            return SpecialForm::Proth { k, n };
+        }
+        SpecialForm::General
+    }
+}
+
+/// # Proth's Theorem
+/// N = k * 2^n + 1 (k odd, k < 2^n) is prime iff a^((N - 1)/2) ≡ -1 (mod N)
+/// for some a; any a with Jacobi symbol (a/N) = -1 decides it.
+///
+/// ## Arguments
+/// * `k` - The odd multiplier (k < 2^n)
+/// * `n` - The power of two
+pub fn is_prime_proth(k: &BigUint, n: u64) -> bool {
+    assert!(
+        k.is_odd() && k.bits() <= n,
+        "Proth numbers need an odd k < 2^n"
+    );
+    let value = (k << n) + 1u32;
+    // Squares Have No Non-Residue
+    let root = value.sqrt();
+    if &root * &root == value {
+        return false;
+    }
+    let minus_one = &value - 1u32;
+    let half = &minus_one >> 1;
+    let mut a = BigUint::from(2u32);
+    loop {
+        match jacobi_symbol(&a, &value) {
+            -1 => return a.modpow(&half, &value) == minus_one,
+            0 => return a == value,
+            _ => a += 1u32,
+        }
+    }
+}
+
+/// # Pépin's Test
+/// F_m = 2^(2^m) + 1 (m ≥ 1) is prime iff 3^((F_m - 1)/2) ≡ -1 (mod F_m).
+///
+/// ## Arguments
+/// * `m` - The index of the Fermat number
+pub fn is_fermat_prime(m: u32) -> bool {
+    if m == 0 {
+        return true;
+    }
+    let fermat = (BigUint::one() << (1u64 << m)) + 1u32;
+    let minus_one = &fermat - 1u32;
+    BigUint::from(3u32).modpow(&(&minus_one >> 1), &fermat) == minus_one
+}
+
+/// Computes the Lucas sequence V_k(P, 1) mod N with the ladder
+/// V_2j = V_j^2 - 2 and V_2j+1 = V_j V_j+1 - P
+fn lucas_v(k: &BigUint, p: &BigUint, modulus: &BigUint) -> BigUint {
+    let two = BigUint::from(2u32);
+    let (mut low, mut high) = (two.clone(), p % modulus);
+    for bit in (0..k.bits()).rev() {
+        if k.bit(bit) {
+            low = (&low * &high + modulus - p % modulus) % modulus;
+            high = (&high * &high + modulus - &two) % modulus;
+        } else {
+            high = (&low * &high + modulus - p % modulus) % modulus;
+            low = (&low * &low + modulus - &two) % modulus;
+        }
+    }
+    low
+}
+
+/// # Lucas-Lehmer-Riesel Test
+/// N = k * 2^n - 1 (k odd, k < 2^n) is prime iff u_(n-2) ≡ 0 (mod N), where
+/// u_0 = V_k(P, 1), u_(i+1) = u_i^2 - 2 and P satisfies ((P - 2)/N) = 1 and
+/// ((P + 2)/N) = -1 (Rödseth).
+///
+/// ## Arguments
+/// * `k` - The odd multiplier (k < 2^n)
+/// * `n` - The power of two
+pub fn is_prime_llr(k: &BigUint, n: u64) -> bool {
+    assert!(
+        k.is_odd() && k.bits() <= n,
+        "Riesel numbers need an odd k < 2^n"
+    );
+    let value = (k << n) - 1u32;
+    if value < BigUint::from(8u32) {
+        return value == BigUint::from(3u32) || value == BigUint::from(7u32);
+    }
+    // Find the Lucas Parameter
+    let mut parameter = None;
+    for p in 3..LLR_PARAMETER_LIMIT {
+        let below = BigUint::from(p - 2);
+        let above = BigUint::from(p + 2);
+        match (jacobi_symbol(&below, &value), jacobi_symbol(&above, &value)) {
+            (0, _) => return below == value,
+            (_, 0) => return above == value,
+            (1, -1) => {
+                parameter = Some(BigUint::from(p));
+                break;
+            }
+            _ => {}
+        }
+    }
+    let parameter = match parameter {
+        Some(parameter) => parameter,
+        None => return false,
+    };
+    let two = BigUint::from(2u32);
+    let mut u = lucas_v(k, &parameter, &value);
+    for _ in 0..n - 2 {
+        u = (&u * &u + &value - &two) % &value;
+    }
+    u.is_zero()
+}
+
+/// # Special Form Primality Test
+/// Routes Mersenne, Fermat, Proth and Riesel numbers to their deterministic
+/// tests. Other numbers fall back to the exact test below 2^64 and to
+/// Miller-Rabin above.
+///
+/// ## Arguments
+/// * `value` - The number to test
+/// * `rounds` - Miller-Rabin rounds for the general case
+pub fn is_prime_special(value: &BigUint, rounds: usize) -> bool {
+    match SpecialForm::detect(value) {
+        SpecialForm::Mersenne(p) => is_mersenne_prime(p),
+        SpecialForm::Fermat(m) => is_fermat_prime(m),
+        SpecialForm::Proth { k, n } => is_prime_proth(&k, n),
+        SpecialForm::Riesel { k, n } => is_prime_llr(&k, n),
+        SpecialForm::General => match value.to_u64_digits().as_slice() {
+            [] => false,
+            [small] => is_prime_u64(*small),
+            _ => is_prime_miller_rabin(value.clone(), rounds),
+        },
+    }
+}
+
+#[test]
+fn test_special_form_detect() {
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(127u32)),
+        SpecialForm::Mersenne(7)
+    );
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(65537u32)),
+        SpecialForm::Fermat(4)
+    );
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(13u32)),
+        SpecialForm::Proth {
+            k: BigUint::from(3u32),
+            n: 2
+        }
+    );
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(23u32)),
+        SpecialForm::Riesel {
+            k: BigUint::from(3u32),
+            n: 3
+        }
+    );
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(101u32)),
+        SpecialForm::General
+    );
+    assert_eq!(
+        SpecialForm::detect(&BigUint::from(1u32)),
+        SpecialForm::General
+    );
+}
+
+#[test]
+fn test_proth_and_llr_small() {
+    // Compare With the Exact u64 Test
+    for k in (1..64u64).step_by(2) {
+        for n in 1..24u64 {
+            if 64 - k.leading_zeros() as u64 > n {
+                continue;
+            }
+            let proth = (k << n) + 1;
+            assert_eq!(
+                is_prime_proth(&BigUint::from(k), n),
+                is_prime_u64(proth),
+                "{}",
+                proth
+            );
+            if n >= 2 {
+                let riesel = (k << n) - 1;
+                assert_eq!(
+                    is_prime_llr(&BigUint::from(k), n),
+                    is_prime_u64(riesel),
+                    "{}",
+                    riesel
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_special_forms_large() {
+    // 3 * 2^189 + 1 is Prime, 3 * 2^190 + 1 is not
+    assert!(is_prime_proth(&BigUint::from(3u32), 189));
+    assert!(!is_prime_proth(&BigUint::from(3u32), 190));
+    // 3 * 2^n - 1 is Prime for n = 143 and n = 206
+    assert!(is_prime_llr(&BigUint::from(3u32), 143));
+    assert!(is_prime_llr(&BigUint::from(3u32), 206));
+    assert!(!is_prime_llr(&BigUint::from(3u32), 145));
+    // F_0 to F_4 are Prime, F_5 to F_7 are not
+    assert!((0..5).all(is_fermat_prime));
+    assert!(!(5..8).any(is_fermat_prime));
+    // The Router Agrees
+    let proth = (BigUint::from(3u32) << 189u32) + 1u32;
+    assert!(is_prime_special(&proth, 10));
+    assert!(is_prime_special(&((BigUint::one() << 127u32) - 1u32), 10));
+    assert!(!is_prime_special(&((BigUint::one() << 128u32) + 1u32), 10));
+}
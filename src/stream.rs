@@ -0,0 +1,377 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use num::BigUint;
+
+use crate::{
+    generator::{GenerationError, Limits, DEFAULT_SECURITY_BITS},
+    pipeline::Pipeline,
+    prime::{small_odd_primes, walk_sieved, PrimalityTest, SMALL_PRIMES_BOUND},
+    random::{Lcg, RandomGenerator},
+    rounds::miller_rabin_rounds,
+    sieve::PrimeIter,
+};
+
+/// What every worker shares
+struct Shared {
+    bits: usize,
+    rng: Mutex<Box<dyn RandomGenerator>>,
+    test: Box<dyn PrimalityTest>,
+    rounds: usize,
+    small_primes: Vec<u32>,
+    stop: AtomicBool,
+}
+
+/// # Prime Stream
+/// Endless iterator of random primes of exactly `bits` bits. Worker threads
+/// are started on the first call to `next` and live as long as the stream.
+/// Each one draws a random odd start, walks it upwards skipping candidates
+/// with a small factor (the small prime table is built once), and sends the
+/// first prime found before drawing a new start. Dropping the stream stops
+/// and joins the workers. With a single thread and a seeded generator the
+/// sequence is reproducible.
+///
+/// Defaults: a time seeded `Lcg`, the default `Pipeline` with Miller-Rabin
+/// rounds from `miller_rabin_rounds(bits, DEFAULT_SECURITY_BITS)` and a
+/// single thread.
+pub struct PrimeStream {
+    bits: usize,
+    rng: Option<Box<dyn RandomGenerator>>,
+    test: Option<Box<dyn PrimalityTest>>,
+    rounds: usize,
+    threads: usize,
+    shared: Option<Arc<Shared>>,
+    receiver: Option<Receiver<BigUint>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl PrimeStream {
+    /// A stream of `bits`-bit primes (`bits` ≥ 2)
+    pub fn new(bits: usize) -> PrimeStream {
+        assert!(bits >= 2, "primes have at least 2 bits");
+        PrimeStream {
+            bits,
+            rng: Some(Box::new(Lcg::from_time())),
+            test: Some(Box::new(Pipeline::default())),
+            rounds: miller_rabin_rounds(bits, DEFAULT_SECURITY_BITS).rounds,
+            threads: 1,
+            shared: None,
+            receiver: None,
+            workers: Vec::new(),
+        }
+    }
+
+    /// Sets the source of random starts
+    pub fn rng<R: RandomGenerator + 'static>(mut self, rng: R) -> PrimeStream {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Sets the primality test
+    pub fn test<T: PrimalityTest + 'static>(mut self, test: T) -> PrimeStream {
+        self.test = Some(Box::new(test));
+        self
+    }
+
+    /// Sets the rounds passed to the primality test
+    pub fn rounds(mut self, rounds: usize) -> PrimeStream {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the number of worker threads
+    pub fn threads(mut self, threads: usize) -> PrimeStream {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Builds the shared state and spawns the workers
+    fn start(&mut self) {
+        // Only Use Primes Smaller Than Any Candidate
+        let bits = self.bits;
+        let small_primes = small_odd_primes(SMALL_PRIMES_BOUND)
+            .into_iter()
+            .filter(|&prime| ((32 - prime.leading_zeros()) as usize) < bits)
+            .collect();
+        let shared = Arc::new(Shared {
+            bits,
+            rng: Mutex::new(self.rng.take().unwrap()),
+            test: self.test.take().unwrap(),
+            rounds: self.rounds,
+            small_primes,
+            stop: AtomicBool::new(false),
+        });
+        let (sender, receiver) = sync_channel(self.threads);
+        self.workers = (0..self.threads)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                let sender = sender.clone();
+                thread::spawn(move || worker(&shared, &sender))
+            })
+            .collect();
+        self.shared = Some(shared);
+        self.receiver = Some(receiver);
+    }
+}
+
+/// Searches primes until the stream is dropped
+fn worker(shared: &Shared, sender: &SyncSender<BigUint>) {
+    let bits = shared.bits;
+    while !shared.stop.load(Ordering::Relaxed) {
+        // Draw a Random Odd Start
+        let mut start = shared.rng.lock().unwrap().gen_bits(bits);
+        start.set_bit(bits as u64 - 1, true);
+        start.set_bit(0, true);
+        // Walk by 2 Within the Bit Length
+        let sent = walk_sieved(&start, bits, &shared.small_primes, |maybe_prime| {
+            if shared.stop.load(Ordering::Relaxed) {
+                return Some(false);
+            }
+            let maybe_prime = maybe_prime?;
+            if !shared.test.is_prime(maybe_prime, shared.rounds) {
+                return None;
+            }
+            // Stop When the Stream is Gone
+            Some(sender.send(maybe_prime.clone()).is_ok())
+        });
+        if sent == Some(false) {
+            return;
+        }
+    }
+}
+
+impl Iterator for PrimeStream {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        if self.receiver.is_none() {
+            self.start();
+        }
+        self.receiver.as_ref().unwrap().recv().ok()
+    }
+}
+
+impl Drop for PrimeStream {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.shared {
+            shared.stop.store(true, Ordering::Relaxed);
+        }
+        // Unblock Workers Waiting to Send
+        self.receiver.take();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+/// Consecutive rejected primes after which a spaced batch starts over
+pub const SPACING_RESTART_LIMIT: usize = 1 << 8;
+
+/// Restarts after which a spaced batch above 32 bits gives up
+pub const SPACING_MAX_RESTARTS: usize = 1 << 3;
+
+/// # Batch Prime Generation
+/// Returns `count` distinct `bits`-bit primes in increasing order, found by
+/// a `PrimeStream` with one worker per available core.
+///
+/// ## Arguments
+/// * `count` - The number of primes
+/// * `bits` - The bit length of every prime
+/// * `limits` - When to give up, counting every prime the stream yields
+pub fn gen_primes(
+    count: usize,
+    bits: usize,
+    limits: &Limits,
+) -> Result<Vec<BigUint>, GenerationError> {
+    gen_primes_spaced(count, bits, &BigUint::from(1u32), limits)
+}
+
+/// Lower bound on the number of `bits`-bit primes, from
+/// x / ln x < π(x) < 1.25506 x / ln x (x ≥ 17)
+fn primes_with_bits_lower_bound(bits: usize) -> f64 {
+    if bits < 6 {
+        return 0.0;
+    }
+    let high = (bits as f64).exp2();
+    let low = high / 2.0;
+    high / high.ln() - 1.25506 * low / low.ln()
+}
+
+/// # Spaced Batch Prime Generation
+/// Like `gen_primes`, but any two primes differ by at least `min_distance`.
+/// Primes colliding with (or too close to) an accepted one are discarded, so
+/// the result never depends on how the workers interleave. As accepted
+/// primes can block the remaining room, a batch that keeps rejecting (see
+/// `SPACING_RESTART_LIMIT`) starts over. Fails if the
+/// constraints can not be met: exactly for bit lengths up to 32 (greedy
+/// spacing over the sieved primes is optimal), and when the bit length can
+/// not hold `count` points `min_distance` apart above that. Above 32 bits it
+/// also gives up after `SPACING_MAX_RESTARTS` restarts, and at any size once
+/// one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `count` - The number of primes
+/// * `bits` - The bit length of every prime
+/// * `min_distance` - The smallest allowed difference between two primes
+/// * `limits` - When to give up, counting every prime the stream yields
+pub fn gen_primes_spaced(
+    count: usize,
+    bits: usize,
+    min_distance: &BigUint,
+    limits: &Limits,
+) -> Result<Vec<BigUint>, GenerationError> {
+    if bits < 2 {
+        return Err(GenerationError::InvalidBitLength(bits));
+    }
+    // Check the Constraints Can be Met, Odd Primes are Always 2 Apart
+    let spaced = *min_distance > BigUint::from(2u32);
+    if bits <= 32 && (spaced || count as f64 > primes_with_bits_lower_bound(bits)) {
+        let mut available = 0;
+        let mut last: Option<u64> = None;
+        let primes = PrimeIter::starting_at(1u64 << (bits - 1)).take_while(|&p| p < 1u64 << bits);
+        for prime in primes {
+            if last.is_none_or(|last| BigUint::from(prime - last) >= *min_distance) {
+                available += 1;
+                last = Some(prime);
+            }
+            if available >= count {
+                break;
+            }
+        }
+        if available < count {
+            return Err(GenerationError::NotEnoughPrimes(count));
+        }
+    } else if count > 1 && min_distance * (count - 1) >= BigUint::from(1u32) << (bits - 1) {
+        return Err(GenerationError::NotEnoughPrimes(count));
+    }
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut stream = PrimeStream::new(bits).threads(threads);
+    let mut primes: BTreeSet<BigUint> = BTreeSet::new();
+    let (mut drawn, mut rejected, mut restarts) = (0, 0, 0);
+    while primes.len() < count {
+        limits.check(drawn)?;
+        drawn += 1;
+        let prime = stream.next().unwrap();
+        // Reject Duplicates and Close Neighbours
+        let below = if prime >= *min_distance {
+            &prime - min_distance + 1u32
+        } else {
+            BigUint::from(0u32)
+        };
+        let above: BigUint = &prime + min_distance;
+        if primes.range(below..above).next().is_none() {
+            primes.insert(prime);
+            rejected = 0;
+        } else {
+            rejected += 1;
+            // Distinct Primes Alone Never Block Each Other
+            if rejected > SPACING_RESTART_LIMIT && *min_distance > BigUint::from(1u32) {
+                primes.clear();
+                rejected = 0;
+                restarts += 1;
+                // Only the Sieved Sizes Are Known to be Feasible
+                if bits > 32 && restarts > SPACING_MAX_RESTARTS {
+                    return Err(GenerationError::NotEnoughPrimes(count));
+                }
+            }
+        }
+    }
+    Ok(primes.into_iter().collect())
+}
+
+#[test]
+fn test_prime_stream() {
+    use crate::prime::{is_prime_bpsw, Bpsw};
+    let primes: Vec<BigUint> = PrimeStream::new(128)
+        .rng(Lcg::new(BigUint::from(3u32)))
+        .take(20)
+        .collect();
+    for prime in &primes {
+        assert!(prime.bits() == 128 && is_prime_bpsw(prime));
+    }
+    let mut sorted = primes.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), 20);
+    // Reproducible With One Thread
+    let again: Vec<BigUint> = PrimeStream::new(128)
+        .rng(Lcg::new(BigUint::from(3u32)))
+        .take(20)
+        .collect();
+    assert_eq!(primes, again);
+    // Several Workers
+    let mut stream = PrimeStream::new(256).test(Bpsw).threads(4);
+    for _ in 0..10 {
+        let prime = stream.next().unwrap();
+        assert!(prime.bits() == 256 && is_prime_bpsw(&prime));
+    }
+    assert!(PrimeStream::new(2)
+        .take(3)
+        .all(|prime| prime == BigUint::from(3u32)));
+}
+
+#[test]
+fn test_gen_primes() {
+    use crate::prime::is_prime_bpsw;
+    use crate::sieve::primes_in_range;
+    let primes = gen_primes(50, 64, &Limits::none()).unwrap();
+    assert_eq!(primes.len(), 50);
+    assert!(primes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(primes
+        .iter()
+        .all(|prime| prime.bits() == 64 && is_prime_bpsw(prime)));
+    // Every 8-Bit Prime (There Are 23)
+    let all = gen_primes(23, 8, &Limits::none()).unwrap();
+    assert_eq!(
+        all,
+        primes_in_range(128, 255)
+            .into_iter()
+            .map(BigUint::from)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        gen_primes(24, 8, &Limits::none()),
+        Err(GenerationError::NotEnoughPrimes(24))
+    );
+    // Few Primes of a Sieved Size Need No Feasibility Sieve
+    let primes = gen_primes(2, 32, &Limits::none()).unwrap();
+    assert!(primes.iter().all(|prime| prime.bits() == 32));
+    // The Bound Never Exceeds the Actual Count
+    for bits in 6..=24 {
+        let actual = primes_in_range(1 << (bits - 1), (1 << bits) - 1).len();
+        assert!(
+            primes_with_bits_lower_bound(bits) <= actual as f64,
+            "{}",
+            bits
+        );
+    }
+    // Spacing
+    let distance = BigUint::from(1u64 << 40);
+    let spaced = gen_primes_spaced(20, 64, &distance, &Limits::none()).unwrap();
+    assert!(spaced
+        .windows(2)
+        .all(|pair| &pair[1] - &pair[0] >= distance));
+    // Tight: at Most 4 of the 8-Bit Primes are 30 Apart
+    let spaced = gen_primes_spaced(4, 8, &BigUint::from(30u32), &Limits::none()).unwrap();
+    assert!(spaced
+        .windows(2)
+        .all(|pair| &pair[1] - &pair[0] >= BigUint::from(30u32)));
+    assert!(gen_primes_spaced(5, 8, &BigUint::from(30u32), &Limits::none()).is_err());
+    // Passes the Size Check, but 3 Primes of 33 Bits Can Not be 2^31 - 1 Apart
+    let distance = BigUint::from((1u64 << 31) - 1);
+    assert_eq!(
+        gen_primes_spaced(3, 33, &distance, &Limits::none()),
+        Err(GenerationError::NotEnoughPrimes(3))
+    );
+    assert_eq!(
+        gen_primes_spaced(3, 33, &distance, &Limits::none().max_candidates(100)),
+        Err(GenerationError::CandidateLimitReached(100))
+    );
+}
@@ -0,0 +1,138 @@
+use num::{BigUint, Integer};
+
+use crate::{
+    generator::{gen_prime_in_range, GenerationError, Limits, DEFAULT_SECURITY_BITS},
+    prime::is_prime_miller_rabin,
+    random::RandomGenerator,
+    rounds::miller_rabin_rounds,
+};
+
+/// A strong prime p with the primes of its construction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrongPrime {
+    /// The prime
+    pub p: BigUint,
+    /// Large prime factor of p - 1
+    pub r: BigUint,
+    /// Large prime factor of p + 1
+    pub s: BigUint,
+    /// Large prime factor of r - 1
+    pub t: BigUint,
+}
+
+/// Random prime with exactly `bits` bits
+fn gen_prime_bits<R: RandomGenerator + ?Sized>(
+    bits: usize,
+    rng: &mut R,
+) -> Result<BigUint, GenerationError> {
+    let lo = BigUint::from(1u32) << (bits - 1);
+    let hi = (BigUint::from(1u32) << bits) - 1u32;
+    gen_prime_in_range(&lo, &hi, rng)
+}
+
+/// # Strong Prime Generation (Gordon)
+/// 1. Draws primes s and t of about `bits`/2 - 16 and `bits`/2 - 24 bits.
+/// 2. Finds the first prime r = 2it + 1 from a random i.
+/// 3. Sets p0 = 2 (s^(r-2) mod r) s - 1, so p0 ≡ 1 (mod r) and p0 ≡ -1 (mod s).
+/// 4. Draws a random x in [√2 · 2^(`bits`-1), 2^`bits`) and finds the first prime
+///    p = p0 + 2jrs at or above x, so the top bits of p are not fixed.
+///
+/// Fails on bit lengths below 64 or once one of the `limits` is hit.
+///
+/// ## Arguments
+/// * `bits` - The bit length of p (at least 64)
+/// * `rng` - The source of randomness
+/// * `limits` - When to give up, counting every candidate for r and p
+pub fn gen_strong_prime<R: RandomGenerator + ?Sized>(
+    bits: usize,
+    rng: &mut R,
+    limits: &Limits,
+) -> Result<StrongPrime, GenerationError> {
+    if bits < 64 {
+        return Err(GenerationError::InvalidBitLength(bits));
+    }
+    let rounds = miller_rabin_rounds(bits, DEFAULT_SECURITY_BITS).rounds;
+    // ⌈√2 · 2^(bits-1)⌉ = ⌊√(2^(2 bits - 1))⌋ + 1, the Square Root Is Irrational
+    let low = (BigUint::from(1u32) << (2 * bits - 1)).sqrt() + 1u32;
+    let width = (BigUint::from(1u32) << bits) - &low;
+    let mut candidates: u64 = 0;
+    loop {
+        // Auxiliary Primes s and t
+        let s = gen_prime_bits(bits / 2 - 16, rng)?;
+        let t = gen_prime_bits(bits / 2 - 24, rng)?;
+        // r = 2it + 1 Prime
+        let mut i = rng.gen_bits(12) + 1u32;
+        let r = loop {
+            limits.check(candidates)?;
+            candidates += 1;
+            let r: BigUint = &i * &t * 2u32 + 1u32;
+            if is_prime_miller_rabin(r.clone(), rounds) {
+                break r;
+            }
+            i += 1u32;
+        };
+        // p0 ≡ 1 (mod r), p0 ≡ -1 (mod s)
+        let s_inverse = s.modpow(&(&r - 2u32), &r);
+        let p0: BigUint = &s_inverse * &s * 2u32 - 1u32;
+        // Walk p = p0 + 2jrs From a Random Start x
+        let start = &low + rng.gen_below(&width);
+        let step: BigUint = &r * &s * 2u32;
+        let j = if p0 < start {
+            (&start - &p0).div_ceil(&step)
+        } else {
+            BigUint::from(0u32)
+        };
+        let mut p = &p0 + &j * &step;
+        while p.bits() as usize == bits {
+            limits.check(candidates)?;
+            candidates += 1;
+            if is_prime_miller_rabin(p.clone(), rounds) {
+                return Ok(StrongPrime { p, r, s, t });
+            }
+            p += &step;
+        }
+    }
+}
+
+#[test]
+fn test_gen_strong_prime() {
+    use crate::prime::is_prime_bpsw;
+    use crate::random::Lcg;
+    let mut rng = Lcg::new(BigUint::from(31337u32));
+    for bits in [64, 128, 512] {
+        let StrongPrime { p, r, s, t } = gen_strong_prime(bits, &mut rng, &Limits::none()).unwrap();
+        assert_eq!(p.bits(), bits as u64);
+        for prime in [&p, &r, &s, &t] {
+            assert!(is_prime_bpsw(prime), "{}", prime);
+        }
+        let zero = BigUint::from(0u32);
+        assert_eq!((&p - 1u32) % &r, zero);
+        assert_eq!((&p + 1u32) % &s, zero);
+        assert_eq!((&r - 1u32) % &t, zero);
+        assert!(s.bits() as usize >= bits / 2 - 16 && t.bits() as usize >= bits / 2 - 24);
+    }
+    assert_eq!(
+        gen_strong_prime(32, &mut rng, &Limits::none()),
+        Err(GenerationError::InvalidBitLength(32))
+    );
+    let limits = Limits::none().max_candidates(3);
+    assert_eq!(
+        gen_strong_prime(512, &mut rng, &limits),
+        Err(GenerationError::CandidateLimitReached(3))
+    );
+}
+
+#[test]
+fn test_gen_strong_prime_random_start() {
+    use crate::random::Lcg;
+    let bits = 128usize;
+    let low = (BigUint::from(1u32) << (2 * bits - 1)).sqrt();
+    let mut top_bits = std::collections::HashSet::new();
+    for seed in 0..8u32 {
+        let mut rng = Lcg::new(BigUint::from(seed));
+        let StrongPrime { p, .. } = gen_strong_prime(bits, &mut rng, &Limits::none()).unwrap();
+        assert!(p > low, "{}", p);
+        top_bits.insert(&p >> (bits - 8));
+    }
+    assert!(top_bits.len() > 1);
+}